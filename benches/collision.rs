@@ -0,0 +1,72 @@
+// how the collision pass scales with the number of balls, run with `cargo bench`
+// the systems run against a bare World, no window, renderer or audio involved
+use amethyst::{
+    core::Transform,
+    ecs::prelude::{Builder, RunNow, System, World, WorldExt},
+    shrev::EventChannel,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use amethyst_game::physics::{Collider, Contact, Velocity};
+use amethyst_game::pong::BALL_RADIUS;
+use amethyst_game::systems::CollisionSystem;
+
+// balls laid out on a square grid with a bit of overlap here and there,
+// the area grows with the count so the density stays roughly the same as a busy pong arena
+fn world_with_balls(count: usize) -> World {
+    let mut world = World::new();
+    world.register::<Collider>();
+    world.register::<Velocity>();
+    world.register::<Transform>();
+    world.insert(EventChannel::<Contact>::new());
+
+    let per_row = (count as f32).sqrt().ceil() as usize;
+    let spacing = BALL_RADIUS * 2.5;
+    let side = per_row as f32 * spacing;
+    for i in 0..count {
+        let mut transform = Transform::default();
+        // every seventh ball nudged into its neighbour so there are contacts to report
+        let nudge = if i % 7 == 0 { spacing * 0.5 } else { 0.0 };
+        transform.set_translation_xyz(
+            (i % per_row) as f32 * spacing + nudge,
+            (i / per_row) as f32 * spacing,
+            0.0,
+        );
+        world
+            .create_entity()
+            .with(Collider::circle(BALL_RADIUS))
+            .with(Velocity([75.0, 50.0]))
+            .with(transform)
+            .build();
+    }
+
+    // the arena walls, long static colliders spanning lots of cells
+    for (a, b) in vec![([0.0, 0.0], [side, 0.0]), ([side, side], [0.0, side])] {
+        world
+            .create_entity()
+            .with(Collider::segment(a, b))
+            .with(Transform::default())
+            .build();
+    }
+    world
+}
+
+fn collision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collision");
+    group.sample_size(20);
+    for &count in &[1, 100, 10_000] {
+        let mut world = world_with_balls(count);
+        let mut system = CollisionSystem;
+        System::setup(&mut system, &mut world);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                system.run_now(&world);
+                world.maintain();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, collision);
+criterion_main!(benches);
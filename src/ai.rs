@@ -0,0 +1,288 @@
+use amethyst::{
+    ecs::prelude::{Component, DenseVecStorage, Entity, Join},
+    prelude::*,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::pong::{Paddle, Side};
+use crate::stats::{DifficultySample, MatchStats};
+
+// contents of config/ai.ron, how well the computer plays
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiConfig {
+    pub difficulty: Difficulty,
+    pub easiest: AiSkill, // how the computer plays at level 0
+    pub hardest: AiSkill, // and at level 1, levels in between are a mix of the two
+    pub adaptive: AdaptiveCurve,
+    pub profiles: Vec<AiProfile>, // computer players to fill a tournament with
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        AiConfig {
+            difficulty: Difficulty::Normal,
+            easiest: AiSkill {
+                reaction: 0.45,
+                error: 10.0,
+                speed: 0.6,
+            },
+            hardest: AiSkill {
+                reaction: 0.05,
+                error: 1.0,
+                speed: 1.0,
+            },
+            adaptive: AdaptiveCurve::default(),
+            profiles: vec![
+                AiProfile::new("Rookie", Difficulty::Easy),
+                AiProfile::new("Club player", Difficulty::Normal),
+                AiProfile::new("Champion", Difficulty::Hard),
+                AiProfile::new("Chameleon", Difficulty::Adaptive),
+            ],
+        }
+    }
+}
+
+// a named computer player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiProfile {
+    pub name: String,
+    pub difficulty: Difficulty,
+}
+
+impl AiProfile {
+    pub fn new(name: &str, difficulty: Difficulty) -> Self {
+        AiProfile {
+            name: name.to_string(),
+            difficulty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Adaptive, // starts at `adaptive.start` and follows the match, see AdaptiveCurve
+}
+
+impl Difficulty {
+    // where the preset sits between easiest (0) and hardest (1)
+    pub fn level(self, curve: &AdaptiveCurve) -> f32 {
+        match self {
+            Difficulty::Easy => 0.0,
+            Difficulty::Normal => 0.5,
+            Difficulty::Hard => 1.0,
+            Difficulty::Adaptive => curve.start,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AiSkill {
+    pub reaction: f32, // seconds before the paddle starts moving once the ball heads its way
+    pub error: f32,    // the most it misjudges where the ball will arrive, in units
+    pub speed: f32,    // times the paddle's own speed
+}
+
+impl AiSkill {
+    // `level` 0 is `self`, 1 is `other`
+    pub fn mix(&self, other: &AiSkill, level: f32) -> AiSkill {
+        let level = level.max(0.0).min(1.0);
+        let mix = |a: f32, b: f32| a + (b - a) * level;
+        AiSkill {
+            reaction: mix(self.reaction, other.reaction),
+            error: mix(self.error, other.error),
+            speed: mix(self.speed, other.speed),
+        }
+    }
+}
+
+// how the adaptive difficulty follows the match, it's re-aimed after every point:
+//     target = start + per_point * (player's lead in points)
+//                    + per_return * (average returns per rally - rally_target)
+// so the computer gets better while the player is ahead or rallying comfortably,
+// and worse while it's running away with the match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveCurve {
+    pub start: f32,
+    pub per_point: f32,
+    pub rally_target: f32,
+    pub per_return: f32,
+    pub rally_window: usize, // how many of the latest rallies are averaged
+    pub step: f32,           // the most the level moves after one point
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for AdaptiveCurve {
+    fn default() -> Self {
+        AdaptiveCurve {
+            start: 0.4,
+            per_point: 0.1,
+            rally_target: 4.0,
+            per_return: 0.05,
+            rally_window: 5,
+            step: 0.1,
+            min: 0.0,
+            max: 1.0,
+        }
+    }
+}
+
+impl AdaptiveCurve {
+    // `rallies` is every rally of the match so far, oldest first
+    pub fn target(&self, lead: i32, rallies: &[u32]) -> f32 {
+        let recent = &rallies[rallies.len().saturating_sub(self.rally_window)..];
+        let average = if recent.is_empty() {
+            self.rally_target
+        } else {
+            recent.iter().sum::<u32>() as f32 / recent.len() as f32
+        };
+        (self.start
+            + self.per_point * lead as f32
+            + self.per_return * (average - self.rally_target))
+            .max(self.min)
+            .min(self.max)
+    }
+}
+
+// a paddle the AiSystem steers instead of the keyboard
+#[derive(Debug)]
+pub struct AiPaddle {
+    pub difficulty: Difficulty,
+    pub level: f32,     // 0 plays like `easiest`, 1 like `hardest`
+    pub tracking: bool, // a ball is on its way, the paddle has picked its target
+    pub reaction: f32,  // time left before it reacts to that ball
+    pub aim_error: f32, // how far off its guess of the landing spot is
+}
+
+impl Component for AiPaddle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// how far into the match the adaptive levels have been adjusted, reset every match
+#[derive(Debug, Default)]
+pub struct AiState {
+    pub points_seen: usize, // rallies in the match stats
+}
+
+// hands the paddle on `side` to the computer, call after initialise_paddles
+pub fn initialise_ai(world: &mut World, side: Side, difficulty: Difficulty) {
+    world.register::<AiPaddle>();
+    let level = {
+        let config = world.read_resource::<AiConfig>();
+        difficulty.level(&config.adaptive)
+    };
+    *world.write_resource::<AiState>() = AiState::default();
+
+    let paddles = {
+        let entities = world.entities();
+        let paddles = world.read_storage::<Paddle>();
+        (&entities, &paddles)
+            .join()
+            .filter(|(_, paddle)| paddle.side == side)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<Entity>>()
+    };
+    let mut ais = world.write_storage::<AiPaddle>();
+    for paddle in paddles {
+        let ai = AiPaddle {
+            difficulty,
+            level,
+            tracking: false,
+            reaction: 0.0,
+            aim_error: 0.0,
+        };
+        if let Err(err) = ais.insert(paddle, ai) {
+            warn!("Could not hand paddle to the computer: {}", err);
+        }
+    }
+
+    let mut stats = world.write_resource::<MatchStats>();
+    stats.computers.push((side, difficulty));
+    stats.difficulty.push(DifficultySample {
+        time: 0.0,
+        side,
+        level,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a preset that doesn't span the whole range, so the bounds are its own
+    fn curve() -> AdaptiveCurve {
+        AdaptiveCurve {
+            min: 0.2,
+            max: 0.8,
+            ..AdaptiveCurve::default()
+        }
+    }
+
+    #[test]
+    fn target_starts_at_start_on_an_even_match() {
+        let curve = curve();
+        assert!((curve.target(0, &[]) - curve.start).abs() < 1e-5);
+        assert!((curve.target(0, &[4, 4, 4]) - curve.start).abs() < 1e-5);
+    }
+
+    #[test]
+    fn target_stays_within_the_preset_bounds() {
+        let curve = curve();
+        for lead in -20..=20 {
+            for &rallies in &[&[][..], &[0, 0, 0][..], &[30, 30, 30][..]] {
+                let target = curve.target(lead, rallies);
+                assert!(
+                    target >= curve.min && target <= curve.max,
+                    "lead {} rallies {:?} gave {}",
+                    lead,
+                    rallies,
+                    target
+                );
+            }
+        }
+        assert!((curve.target(20, &[30]) - curve.max).abs() < 1e-5);
+        assert!((curve.target(-20, &[0]) - curve.min).abs() < 1e-5);
+    }
+
+    #[test]
+    fn target_rises_while_the_player_runs_away_with_it() {
+        let curve = curve();
+        let mut previous = curve.target(0, &[]);
+        let mut rallies = Vec::new();
+        for lead in 1..=3 {
+            rallies.push(4);
+            let target = curve.target(lead, &rallies);
+            assert!(target > previous, "lead {} gave {}", lead, target);
+            previous = target;
+        }
+    }
+
+    #[test]
+    fn target_falls_while_the_computer_runs_away_with_it() {
+        let curve = curve();
+        let mut previous = curve.target(0, &[]);
+        let mut rallies = Vec::new();
+        for lead in 1..=2 {
+            rallies.push(4);
+            let target = curve.target(-lead, &rallies);
+            assert!(target < previous, "lead {} gave {}", -lead, target);
+            previous = target;
+        }
+    }
+
+    #[test]
+    fn target_follows_only_the_latest_rallies() {
+        let curve = curve();
+        let long_then_short = [20, 20, 20, 1, 1, 1, 1, 1];
+        let short = [1, 1, 1, 1, 1];
+        assert!((curve.target(0, &long_then_short) - curve.target(0, &short)).abs() < 1e-5);
+        assert!(curve.target(0, &[8, 8]) > curve.start);
+        assert!(curve.target(0, &[1, 1]) < curve.start);
+    }
+}
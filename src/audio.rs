@@ -1,71 +1,302 @@
 use amethyst::{
-    assets::{AssetStorage, Loader},
-    audio::{output::Output, AudioSink, OggFormat, Source, SourceHandle},
+    assets::{Loader, ProgressCounter},
+    audio::{FlacFormat, Mp3Format, OggFormat, SourceHandle, WavFormat},
     ecs::{World, WorldExt},
 };
+use log::warn;
+use rodio::Device;
+use serde::{Deserialize, Serialize};
 
-const BOUNCE_SOUND: &str = "audio/bounce.ogg";
-const SCORE_SOUND: &str = "audio/score.ogg";
+use std::{collections::HashMap, path::Path};
 
-const MUSIC_TRACKS: &[&str] = &[
-    "audio/Computer_Music_All-Stars_-_Wheres_My_Jetpack.ogg",
-    "audio/Computer_Music_All-Stars_-_Albatross_v2.ogg",
-];
+// names the gameplay events are played under, the manifest decides which file each one is
+pub const WALL_BOUNCE_SOUND: &str = "wall_bounce";
+pub const PADDLE_BOUNCE_SOUND: &str = "paddle_bounce";
+pub const SCORE_SOUND: &str = "score";
+pub const BRICK_BREAK_SOUND: &str = "brick_break";
 
-use std::{iter::Cycle, vec::IntoIter};
+// at most this many sound effects play at once, the oldest one is cut off to make room
+pub const MAX_VOICES: usize = 8;
 
+// which set of tracks should be playing, states pick one when they start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Playlist {
+    Menu,
+    Match,
+}
+
+// contents of config/music.ron
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MusicConfig {
+    pub shuffle: bool,
+    pub crossfade: f32,    // seconds the old and new track overlap
+    pub duck_volume: f32,  // music volume multiplier while ducked
+    pub duck_seconds: f32, // how long music stays ducked after a point
+    pub playlists: HashMap<Playlist, Vec<String>>, // track names from the audio manifest
+}
+
+impl Default for MusicConfig {
+    fn default() -> Self {
+        MusicConfig {
+            shuffle: false,
+            crossfade: 2.0,
+            duck_volume: 0.3,
+            duck_seconds: 1.5,
+            playlists: HashMap::new(),
+        }
+    }
+}
+
+// runtime playlist state, read and driven by the MusicSystem
 pub struct Music {
-    pub music: Cycle<IntoIter<SourceHandle>>,
+    pub playlists: HashMap<Playlist, Vec<SourceHandle>>,
+    pub current: Playlist,
+    pub shuffle: bool,
+    pub crossfade: f32,
+    pub duck_volume: f32,
+    pub duck_seconds: f32,
+}
+
+impl Music {
+    pub fn tracks(&self, playlist: Playlist) -> &[SourceHandle] {
+        self.playlists
+            .get(&playlist)
+            .map(|tracks| tracks.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+// contents of assets/audio/manifest.ron, every sound and music track the game knows about
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioManifest {
+    pub sounds: HashMap<String, SoundSpec>,
+    pub music: HashMap<String, String>, // track name -> file
+}
+
+// the output device sound effects and music both play on, opened once while loading
+// not inserted at all when there's no device, everything is silent then
+pub struct AudioDevice(pub Device);
+
+pub struct SoundEffect {
+    pub handle: SourceHandle,
+    pub spec: SoundSpec,
 }
 
 pub struct Sounds {
-    pub score_sfx: SourceHandle,
-    pub bounce_sfx: SourceHandle,
+    pub effects: HashMap<String, SoundEffect>,
 }
 
-fn load_audio_track(loader: &Loader, world: &World, file: &str) -> SourceHandle {
-    loader.load(file, OggFormat, (), &world.read_resource())
+impl Sounds {
+    pub fn get(&self, name: &str) -> Option<&SoundEffect> {
+        self.effects.get(name)
+    }
 }
 
-pub fn initialise_audio(world: &mut World) {
-    let (sound_effects, music) = {
-        let loader = world.read_resource::<Loader>();
+// the three volume buses, used by the options screen to pick which one to adjust
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    Master,
+    Music,
+    Sfx,
+}
 
-        let mut sink = world.write_resource::<AudioSink>();
-        sink.set_volume(0.25);
+pub const BUSES: [Bus; 3] = [Bus::Master, Bus::Music, Bus::Sfx];
 
-        let music = MUSIC_TRACKS
-            .iter()
-            .map(|file| load_audio_track(&loader, &world, file))
-            .collect::<Vec<_>>()
-            .into_iter()
-            .cycle();
-        let music = Music { music };
-
-        let sound = Sounds {
-            bounce_sfx: load_audio_track(&loader, &world, BOUNCE_SOUND),
-            score_sfx: load_audio_track(&loader, &world, SCORE_SOUND),
+// volume buses, each sound ends up at `master * bus * sound volume`
+// this is also what gets saved to the user settings file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioMixer {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub master_muted: bool,
+    pub music_muted: bool,
+    pub sfx_muted: bool,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        AudioMixer {
+            master: 1.0,
+            music: 0.25, // used to be the global sink volume
+            sfx: 1.0,
+            master_muted: false,
+            music_muted: false,
+            sfx_muted: false,
+        }
+    }
+}
+
+impl AudioMixer {
+    pub fn music_volume(&self) -> f32 {
+        self.level(Bus::Master) * self.level(Bus::Music)
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.level(Bus::Master) * self.level(Bus::Sfx)
+    }
+
+    // volume of a single bus after muting
+    pub fn level(&self, bus: Bus) -> f32 {
+        if self.muted(bus) {
+            0.0
+        } else {
+            self.volume(bus)
+        }
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        match bus {
+            Bus::Master => self.master,
+            Bus::Music => self.music,
+            Bus::Sfx => self.sfx,
+        }
+    }
+
+    pub fn muted(&self, bus: Bus) -> bool {
+        match bus {
+            Bus::Master => self.master_muted,
+            Bus::Music => self.music_muted,
+            Bus::Sfx => self.sfx_muted,
+        }
+    }
+
+    // nudges a bus volume up or down, kept within 0..1
+    pub fn adjust(&mut self, bus: Bus, amount: f32) {
+        let volume = match bus {
+            Bus::Master => &mut self.master,
+            Bus::Music => &mut self.music,
+            Bus::Sfx => &mut self.sfx,
         };
+        *volume = (*volume + amount).max(0.0).min(1.0);
+    }
 
-        (sound, music)
-    };
+    pub fn toggle_mute(&mut self, bus: Bus) {
+        let muted = match bus {
+            Bus::Master => &mut self.master_muted,
+            Bus::Music => &mut self.music_muted,
+            Bus::Sfx => &mut self.sfx_muted,
+        };
+        *muted = !*muted;
+    }
+}
 
-    world.insert(sound_effects);
-    world.insert(music);
+// a manifest entry: which file to play and how, variations are +/- around the base value
+// so repeated sounds don't all come out identical
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSpec {
+    pub file: String,
+    pub volume: f32,
+    pub volume_variation: f32,
+    pub pitch: f32,
+    pub pitch_variation: f32,
 }
 
-pub fn play_bounce_sound(sounds: &Sounds, storage: &AssetStorage<Source>, output: Option<&Output>) {
-    if let Some(ref output) = output.as_ref() {
-        if let Some(sound) = storage.get(&sounds.bounce_sfx) {
-            output.play_once(sound, 1.0);
+impl Default for SoundSpec {
+    fn default() -> Self {
+        SoundSpec {
+            file: String::new(),
+            volume: 1.0,
+            volume_variation: 0.0,
+            pitch: 1.0,
+            pitch_variation: 0.0,
         }
     }
 }
 
-pub fn play_score_sound(sounds: &Sounds, storage: &AssetStorage<Source>, output: Option<&Output>) {
-    if let Some(ref output) = output.as_ref() {
-        if let Some(sound) = storage.get(&sounds.score_sfx) {
-            output.play_once(sound, 1.0);
+// the loader needs to be told the format, so pick it from the file extension
+fn load_audio_track(
+    loader: &Loader,
+    world: &World,
+    progress: &mut ProgressCounter,
+    file: &str,
+) -> Option<SourceHandle> {
+    let extension = Path::new(file)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    let storage = world.read_resource();
+    match extension.as_ref().map(String::as_str) {
+        Some("ogg") => Some(loader.load(file, OggFormat, progress, &storage)),
+        Some("wav") => Some(loader.load(file, WavFormat, progress, &storage)),
+        Some("flac") => Some(loader.load(file, FlacFormat, progress, &storage)),
+        Some("mp3") => Some(loader.load(file, Mp3Format, progress, &storage)),
+        _ => {
+            warn!("Unsupported audio format, skipping {}", file);
+            None
         }
     }
 }
+
+// starts loading everything in the manifest, `progress` tracks when it's all in
+pub fn initialise_audio(world: &mut World, progress: &mut ProgressCounter) {
+    let (sound_effects, music) = {
+        let loader = world.read_resource::<Loader>();
+        let manifest = world.read_resource::<AudioManifest>();
+        let config = world.read_resource::<MusicConfig>();
+
+        // each track is loaded once, playlists share the handles
+        let tracks = manifest
+            .music
+            .iter()
+            .filter_map(|(name, file)| {
+                load_audio_track(&loader, &world, progress, file)
+                    .map(|handle| (name.clone(), handle))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let playlists = config
+            .playlists
+            .iter()
+            .map(|(playlist, names)| {
+                let handles = names
+                    .iter()
+                    .filter_map(|name| {
+                        let handle = tracks.get(name).cloned();
+                        if handle.is_none() {
+                            warn!("Playlist {:?} refers to unknown track {}", playlist, name);
+                        }
+                        handle
+                    })
+                    .collect::<Vec<_>>();
+                (*playlist, handles)
+            })
+            .collect();
+        let music = Music {
+            playlists,
+            current: Playlist::Menu,
+            shuffle: config.shuffle,
+            crossfade: config.crossfade,
+            duck_volume: config.duck_volume,
+            duck_seconds: config.duck_seconds,
+        };
+
+        let effects = manifest
+            .sounds
+            .iter()
+            .filter_map(|(name, spec)| {
+                load_audio_track(&loader, &world, progress, &spec.file).map(|handle| {
+                    let effect = SoundEffect {
+                        handle,
+                        spec: spec.clone(),
+                    };
+                    (name.clone(), effect)
+                })
+            })
+            .collect();
+        let sound = Sounds { effects };
+
+        (sound, music)
+    };
+
+    world.insert(sound_effects);
+    world.insert(music);
+    match rodio::default_output_device() {
+        Some(device) => world.insert(AudioDevice(device)),
+        None => warn!("No audio output device found, playing without sound"),
+    }
+}
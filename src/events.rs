@@ -0,0 +1,30 @@
+use crate::pong::Side;
+
+// things that happen during a match, published by the gameplay systems via an EventChannel
+// anything interested (audio for now) registers a reader, so gameplay doesn't need to know who's listening
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    // ball bounced off the top or bottom of the arena
+    WallBounce {
+        position: [f32; 2],
+        speed: f32,
+    },
+    // ball was returned by a paddle
+    PaddleBounce {
+        side: Side,
+        position: [f32; 2],
+        speed: f32,
+    },
+    // ball knocked a brick out (breakout)
+    BrickBreak {
+        position: [f32; 2],
+        speed: f32,
+    },
+    // ball left the arena, `side` is the player who got the point
+    Score {
+        side: Side,
+        position: [f32; 2],
+    },
+    // the watchdog took a ball in a bad state out of play, the mode serves a new one
+    BallRemoved,
+}
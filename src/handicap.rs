@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pong::Side;
+
+// evens out a match between players of different skill, everything at its default is a fair game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handicap {
+    pub paddle_height: f32,  // times the normal paddle height
+    pub paddle_speed: f32,   // times the normal paddle speed
+    pub goal_width: f32,     // share of the player's side that is goal, the rest is wall
+    pub starting_score: u32, // points up at the start, games of the first set in tennis
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            paddle_height: 1.0,
+            paddle_speed: 1.0,
+            goal_width: 1.0,
+            starting_score: 0,
+        }
+    }
+}
+
+// the setting for each side, picked on the match setup screen and saved with the user settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handicaps {
+    pub left: Handicap,
+    pub right: Handicap,
+}
+
+impl Handicaps {
+    pub fn side(&self, side: Side) -> &Handicap {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    pub fn side_mut(&mut self, side: Side) -> &mut Handicap {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
+
+// one line of the setup screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    PaddleHeight,
+    PaddleSpeed,
+    GoalWidth,
+    StartingScore,
+}
+
+pub const SETTINGS: [Setting; 4] = [
+    Setting::PaddleHeight,
+    Setting::PaddleSpeed,
+    Setting::GoalWidth,
+    Setting::StartingScore,
+];
+
+impl Setting {
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::PaddleHeight => "Paddle height",
+            Setting::PaddleSpeed => "Paddle speed",
+            Setting::GoalWidth => "Goal width",
+            Setting::StartingScore => "Head start",
+        }
+    }
+
+    // moves the setting one step up (`steps` 1) or down (-1), kept within a playable range
+    pub fn adjust(self, handicap: &mut Handicap, steps: i32) {
+        let steps = steps as f32;
+        match self {
+            Setting::PaddleHeight => {
+                handicap.paddle_height = (handicap.paddle_height + 0.25 * steps).max(0.5).min(2.0)
+            }
+            Setting::PaddleSpeed => {
+                handicap.paddle_speed = (handicap.paddle_speed + 0.25 * steps).max(0.5).min(2.0)
+            }
+            Setting::GoalWidth => {
+                handicap.goal_width = (handicap.goal_width + 0.25 * steps).max(0.25).min(1.0)
+            }
+            Setting::StartingScore => {
+                handicap.starting_score =
+                    (handicap.starting_score as f32 + steps).max(0.0).min(9.0) as u32
+            }
+        }
+    }
+
+    pub fn value_text(self, handicap: &Handicap) -> String {
+        match self {
+            Setting::PaddleHeight => format!("{:.0}%", handicap.paddle_height * 100.0),
+            Setting::PaddleSpeed => format!("{:.0}%", handicap.paddle_speed * 100.0),
+            Setting::GoalWidth => format!("{:.0}%", handicap.goal_width * 100.0),
+            Setting::StartingScore => format!("{}", handicap.starting_score),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `setting` adjusted `steps` times in the direction of `step`
+    fn adjusted(setting: Setting, step: i32, steps: u32) -> Handicap {
+        let mut handicap = Handicap::default();
+        for _ in 0..steps {
+            setting.adjust(&mut handicap, step);
+        }
+        handicap
+    }
+
+    #[test]
+    fn the_default_handicap_is_a_fair_game() {
+        let handicap = Handicap::default();
+        let texts = SETTINGS
+            .iter()
+            .map(|setting| setting.value_text(&handicap))
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["100%", "100%", "100%", "0"]);
+    }
+
+    #[test]
+    fn paddle_scales_move_in_quarters_within_half_and_double() {
+        for &setting in &[Setting::PaddleHeight, Setting::PaddleSpeed] {
+            let scale = |handicap: &Handicap| match setting {
+                Setting::PaddleHeight => handicap.paddle_height,
+                _ => handicap.paddle_speed,
+            };
+            assert!((scale(&adjusted(setting, 1, 1)) - 1.25).abs() < 1e-5);
+            assert!((scale(&adjusted(setting, -1, 1)) - 0.75).abs() < 1e-5);
+            assert!((scale(&adjusted(setting, 1, 10)) - 2.0).abs() < 1e-5);
+            assert!((scale(&adjusted(setting, -1, 10)) - 0.5).abs() < 1e-5);
+        }
+        assert_eq!(
+            Setting::PaddleHeight.value_text(&adjusted(Setting::PaddleHeight, 1, 1)),
+            "125%"
+        );
+    }
+
+    #[test]
+    fn the_goal_only_ever_narrows_down_to_a_quarter() {
+        assert!((adjusted(Setting::GoalWidth, 1, 3).goal_width - 1.0).abs() < 1e-5);
+        assert!((adjusted(Setting::GoalWidth, -1, 1).goal_width - 0.75).abs() < 1e-5);
+        assert!((adjusted(Setting::GoalWidth, -1, 10).goal_width - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn the_head_start_goes_from_nothing_to_nine() {
+        assert_eq!(adjusted(Setting::StartingScore, 1, 3).starting_score, 3);
+        assert_eq!(adjusted(Setting::StartingScore, 1, 20).starting_score, 9);
+        assert_eq!(adjusted(Setting::StartingScore, -1, 1).starting_score, 0);
+        let mut handicap = adjusted(Setting::StartingScore, 1, 20);
+        Setting::StartingScore.adjust(&mut handicap, -1);
+        assert_eq!(handicap.starting_score, 8);
+    }
+
+    #[test]
+    fn each_side_keeps_its_own_handicap() {
+        let mut handicaps = Handicaps::default();
+        Setting::StartingScore.adjust(handicaps.side_mut(Side::Right), 1);
+        assert_eq!(handicaps.side(Side::Right).starting_score, 1);
+        assert_eq!(handicaps.side(Side::Left).starting_score, 0);
+        assert_eq!(handicaps.right.starting_score, 1);
+    }
+}
@@ -0,0 +1,22 @@
+// the game itself lives in this library so that benchmarks (and other tools) can drive
+// its systems without opening a window, main.rs only wires it into an Application
+pub mod ai;
+pub mod audio;
+pub mod events;
+pub mod handicap;
+pub mod loading;
+pub mod menu;
+pub mod modes;
+pub mod modifiers;
+pub mod options;
+pub mod physics;
+pub mod pipeline;
+pub mod pong;
+pub mod profiles;
+pub mod rules;
+pub mod scoring;
+pub mod settings;
+pub mod setup;
+pub mod stats;
+pub mod systems;
+pub mod tournament;
@@ -0,0 +1,236 @@
+use amethyst::{
+    assets::{AssetStorage, Handle, Loader, ProgressCounter},
+    ecs::prelude::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    renderer::{ImageFormat, SpriteSheet, SpriteSheetFormat, Texture},
+    ui::{Anchor, FontHandle, TtfFormat, UiImage, UiText, UiTransform},
+};
+
+use log::{error, warn};
+
+use crate::audio::initialise_audio;
+use crate::menu::MainMenu;
+
+const BAR_WIDTH: f32 = 300.;
+const BAR_HEIGHT: f32 = 20.;
+
+// handles to everything the game states draw with, available once loading is done
+#[derive(Clone)]
+pub struct GameAssets {
+    pub sprite_sheet: Handle<SpriteSheet>,
+    pub font: FontHandle,
+}
+
+// first state: starts every asset load, shows a progress bar and only moves on
+// to the menu once everything has arrived (or to an error screen if something didn't)
+#[derive(Default)]
+pub struct Loading {
+    progress: ProgressCounter,
+    bar: Option<Entity>,
+    entities: Vec<Entity>,
+}
+
+impl SimpleState for Loading {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        let font = load_font(world, &mut self.progress);
+        let sprite_sheet = load_sprite_sheet(world, &mut self.progress);
+        initialise_audio(world, &mut self.progress);
+        world.insert(GameAssets {
+            sprite_sheet,
+            font: font.clone(),
+        });
+
+        // the font is loading too, so the label may pop in a moment after the bar
+        let label = world
+            .create_entity()
+            .with(UiTransform::new(
+                "loading_label".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                40.,
+                1.,
+                BAR_WIDTH,
+                40.,
+            ))
+            .with(UiText::new(
+                font,
+                "Loading...".to_string(),
+                [1., 1., 1., 1.],
+                30.,
+            ))
+            .build();
+        let background = world
+            .create_entity()
+            .with(UiTransform::new(
+                "loading_bar_background".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                0.,
+                1.,
+                BAR_WIDTH,
+                BAR_HEIGHT,
+            ))
+            .with(UiImage::SolidColor([0.2, 0.2, 0.2, 1.]))
+            .build();
+        // grows from the left edge of the background as assets finish
+        let bar = world
+            .create_entity()
+            .with(UiTransform::new(
+                "loading_bar".to_string(),
+                Anchor::Middle,
+                Anchor::MiddleLeft,
+                -BAR_WIDTH * 0.5,
+                0.,
+                2.,
+                0.,
+                BAR_HEIGHT,
+            ))
+            .with(UiImage::SolidColor([1., 1., 1., 1.]))
+            .build();
+
+        self.bar = Some(bar);
+        self.entities = vec![label, background, bar];
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Err(err) = data.world.delete_entities(&self.entities) {
+            warn!("Could not clean up loading screen: {}", err);
+        }
+        self.entities.clear();
+        self.bar = None;
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let total = self.progress.num_assets();
+        let done = self.progress.num_finished() + self.progress.num_failed();
+        let fraction = if total > 0 {
+            done as f32 / total as f32
+        } else {
+            1.0
+        };
+        if let Some(bar) = self.bar {
+            if let Some(transform) = data.world.write_storage::<UiTransform>().get_mut(bar) {
+                transform.width = BAR_WIDTH * fraction;
+            }
+        }
+
+        if !self.progress.is_complete() {
+            return Trans::None;
+        }
+
+        let errors = self.progress.errors();
+        if errors.is_empty() {
+            Trans::Switch(Box::new(MainMenu::default()))
+        } else {
+            let failed = errors
+                .into_iter()
+                .map(|meta| {
+                    error!("Failed to load {}: {}", meta.asset_name, meta.error);
+                    meta.asset_name
+                })
+                .collect();
+            Trans::Switch(Box::new(LoadFailed::new(failed)))
+        }
+    }
+}
+
+// dead end shown when some assets could not be loaded, lists them so it's obvious what's missing
+pub struct LoadFailed {
+    failed: Vec<String>,
+    entities: Vec<Entity>,
+}
+
+impl LoadFailed {
+    pub fn new(failed: Vec<String>) -> Self {
+        LoadFailed {
+            failed,
+            entities: Vec::new(),
+        }
+    }
+}
+
+impl SimpleState for LoadFailed {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        // the font may be one of the failures, in which case the log is all there is
+        let font = world.read_resource::<GameAssets>().font.clone();
+
+        let lines = std::iter::once("Failed to load:".to_string())
+            .chain(self.failed.iter().cloned())
+            .chain(std::iter::once("Press Esc to quit".to_string()));
+        self.entities = lines
+            .enumerate()
+            .map(|(i, line)| {
+                world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        format!("load_failed_{}", i),
+                        Anchor::TopMiddle,
+                        Anchor::TopMiddle,
+                        0.,
+                        -20. - 30. * i as f32,
+                        1.,
+                        480.,
+                        30.,
+                    ))
+                    .with(UiText::new(font.clone(), line, [1., 0.3, 0.3, 1.], 18.))
+                    .build()
+            })
+            .collect();
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) || is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
+        }
+        Trans::None
+    }
+}
+
+fn load_font(world: &mut World, progress: &mut ProgressCounter) -> FontHandle {
+    world.read_resource::<Loader>().load(
+        "font/square.ttf",
+        TtfFormat,
+        progress,
+        &world.read_resource(),
+    )
+}
+
+// returns reference, for others to lazy 'read'
+fn load_sprite_sheet(world: &mut World, progress: &mut ProgressCounter) -> Handle<SpriteSheet> {
+    // loads spritesheet needed for graphics
+    // texture contains pixel data
+    // 'texture_handle' is a cloneable ref to texture
+    let texture_handle = {
+        // sharable resource, loaded when app is built
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+        // path, type,
+        loader.load(
+            "texture/pong_spritesheet.png",
+            ImageFormat::default(),
+            &mut *progress,
+            &texture_storage,
+        )
+    };
+
+    let loader = world.read_resource::<Loader>();
+    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
+    loader.load(
+        "texture/pong_spritesheet.ron",
+        SpriteSheetFormat(texture_handle), // refernce to source data?
+        progress,
+        &sprite_sheet_store, // place to store data in?
+    )
+}
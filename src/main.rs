@@ -1,5 +1,7 @@
 use amethyst::{
-    audio::{AudioBundle, DjSystemDesc},
+    assets::Processor,
+    audio::Source,
+    config::Config,
     core::transform::TransformBundle,
     input::{InputBundle, StringBindings},
     prelude::*,
@@ -12,12 +14,15 @@ use amethyst::{
     utils::application_root_dir,
 };
 
-mod audio;
-mod pong;
-mod systems;
-
-use crate::audio::Music;
-use crate::pong::Pong;
+use amethyst_game::ai::AiConfig;
+use amethyst_game::audio::{AudioManifest, MusicConfig};
+use amethyst_game::loading::Loading;
+#[cfg(feature = "rapier")]
+use amethyst_game::physics;
+use amethyst_game::pipeline::{Phase, Pipeline};
+use amethyst_game::rules::Rules;
+use amethyst_game::settings::load_user_settings;
+use amethyst_game::systems;
 
 fn main() -> amethyst::Result<()> {
     amethyst::start_logger(Default::default());
@@ -25,6 +30,11 @@ fn main() -> amethyst::Result<()> {
     let assets_dir = app_root.join("assets"); // path to assets
     let display_config_path = app_root.join("config").join("display.ron"); // connect /config/display.ron to path
     let binding_path = app_root.join("config").join("bindings.ron"); // connect input bindings
+    let music_config = MusicConfig::load(app_root.join("config").join("music.ron")); // playlists, falls back to silence
+    let audio_manifest = AudioManifest::load(assets_dir.join("audio").join("manifest.ron")); // which sounds and tracks exist
+    let rules = Rules::load(app_root.join("config").join("rules.ron")); // spin and other gameplay tuning
+    let ai_config = AiConfig::load(app_root.join("config").join("ai.ron")); // how well the computer plays
+    let user_settings = load_user_settings(); // volumes and handicaps saved from the last session
 
     let input_bundle =
         InputBundle::<StringBindings>::new().with_bindings_from_file(binding_path)?;
@@ -44,20 +54,87 @@ fn main() -> amethyst::Result<()> {
         .with_bundle(TransformBundle::new())?
         .with_bundle(input_bundle)? // handles tracking entity positions
         .with_bundle(UiBundle::<StringBindings>::new())?
-        .with_bundle(AudioBundle::default())?
+        // only the loading half of the AudioBundle, our audio systems play everything
+        // on the one output device from initialise_audio
+        .with(Processor::<Source>::new(), "source_processor", &[]);
+
+    // our own systems go through the pipeline, which orders them by phase
+    // (paddles -> movement -> contacts -> bounces -> scoring -> audio), see pipeline.rs
+    let pipeline = Pipeline::new(game_data)
+        .after(&["input_system"])
+        .with(Phase::Control, systems::PaddleSystem, "paddle_system")
+        .with(Phase::Control, systems::AiSystem, "ai_system");
+    let game_data = with_physics(pipeline)
+        .with(Phase::Scoring, systems::GoalSystem, "goal_system")
+        .with(
+            Phase::Scoring,
+            systems::WatchdogSystem::default(),
+            "watchdog_system",
+        )
+        .with(
+            Phase::Presentation,
+            systems::AbilityHudSystem,
+            "ability_hud_system",
+        )
+        .with(
+            Phase::Presentation,
+            systems::ModifierIndicatorSystem,
+            "modifier_indicator_system",
+        )
+        .with_system_desc(
+            Phase::Presentation,
+            systems::AudioSystemDesc::default(),
+            "audio_system",
+        )
+        .with_system_desc(
+            Phase::Presentation,
+            systems::MusicSystemDesc::default(),
+            "music_system",
+        )
         .with_system_desc(
-            DjSystemDesc::new(|music: &mut Music| music.music.next()),
-            "dj_system",
-            &[],
-        )
-        .with(systems::PaddleSystem, "paddle_system", &["input_system"]) // adding a system alone, not bundle, params are dependencies to run before this
-        .with(systems::MoveBallsSystem, "ball_system", &[])
-        .with(systems::BounceSystem, "collision_system", &["ball_system"])
-        .with(systems::WinnerSystem, "winner_system", &["ball_system"]);
+            Phase::Presentation,
+            systems::MatchStatsSystemDesc::default(),
+            "match_stats_system",
+        )
+        .build();
     let mut world = World::new();
-    let mut game = Application::new(assets_dir, Pong::default(), game_data)?; // connect (path_to_assets, State, GameDataBuilder )
-                                                                              // binds OS event loop, state machines, timers, other core components together
+    let mut game = Application::build(assets_dir, Loading::default())? // connect (path_to_assets, State, GameDataBuilder )
+        .with_resource(music_config)
+        .with_resource(audio_manifest)
+        .with_resource(rules)
+        .with_resource(ai_config)
+        .with_resource(user_settings.audio)
+        .with_resource(user_settings.handicaps)
+        .build(game_data)?;
+    // binds OS event loop, state machines, timers, other core components together
 
     game.run(); // simply start loop, until State returns Trans::Quit, or all states popped off State
     Ok(())
 }
+
+// the simple built-in solver, good enough for classic pong
+#[cfg(not(feature = "rapier"))]
+fn with_physics<'a, 'b>(pipeline: Pipeline<'a, 'b>) -> Pipeline<'a, 'b> {
+    pipeline
+        .with(Phase::Integrate, systems::MoveBallsSystem, "ball_system")
+        .with(
+            Phase::Collision,
+            systems::CollisionSystem,
+            "collision_system",
+        )
+        .with_system_desc(
+            Phase::Response,
+            systems::BounceSystemDesc::default(),
+            "bounce_system",
+        )
+}
+
+// rapier moves, collides and bounces everything in one go
+#[cfg(feature = "rapier")]
+fn with_physics<'a, 'b>(pipeline: Pipeline<'a, 'b>) -> Pipeline<'a, 'b> {
+    pipeline.with_system_desc(
+        Phase::Integrate,
+        physics::RapierSystemDesc::default(),
+        "rapier_system",
+    )
+}
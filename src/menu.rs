@@ -0,0 +1,133 @@
+use amethyst::{
+    ecs::prelude::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, UiText, UiTransform},
+};
+
+use log::warn;
+
+use crate::audio::{Music, Playlist};
+use crate::loading::GameAssets;
+use crate::modes::{Breakout, Drill, Squash, DEFAULT_DRILL};
+use crate::options::OptionsMenu;
+use crate::pong::Pong;
+use crate::setup::MatchSetup;
+use crate::tournament::{load_tournament, BracketScreen, TournamentSetup};
+
+const MENU_LINES: &[&str] = &[
+    "PONG",
+    "",
+    "Enter - Play",
+    "C - Play the computer",
+    "B - Breakout",
+    "P - Squash practice",
+    "T - Training drill",
+    "U - Tournament",
+    "O - Options",
+    "Esc - Quit",
+];
+
+// title screen, the match and the options screen are pushed on top of it
+#[derive(Default)]
+pub struct MainMenu {
+    entities: Vec<Entity>,
+}
+
+impl SimpleState for MainMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    // UI is taken down while something else is on top, and rebuilt when we're back
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) || is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
+            if is_key_down(event, VirtualKeyCode::Return) {
+                return Trans::Push(Box::new(MatchSetup::default()));
+            }
+            if is_key_down(event, VirtualKeyCode::C) {
+                return Trans::Push(Box::new(MatchSetup::against_computer()));
+            }
+            if is_key_down(event, VirtualKeyCode::B) {
+                return Trans::Push(Box::new(Pong::new(Box::new(Breakout::default()))));
+            }
+            if is_key_down(event, VirtualKeyCode::P) {
+                return Trans::Push(Box::new(Pong::new(Box::new(Squash::default()))));
+            }
+            if is_key_down(event, VirtualKeyCode::T) {
+                return Trans::Push(Box::new(Pong::new(Box::new(Drill::new(DEFAULT_DRILL)))));
+            }
+            if is_key_down(event, VirtualKeyCode::U) {
+                // carry on with the last tournament if there is one
+                return match load_tournament() {
+                    Some(tournament) => Trans::Push(Box::new(BracketScreen::new(tournament))),
+                    None => Trans::Push(Box::new(TournamentSetup::default())),
+                };
+            }
+            if is_key_down(event, VirtualKeyCode::O) {
+                return Trans::Push(Box::new(OptionsMenu::default()));
+            }
+        }
+        Trans::None
+    }
+}
+
+impl MainMenu {
+    fn show(&mut self, world: &mut World) {
+        world.write_resource::<Music>().current = Playlist::Menu;
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        self.entities = MENU_LINES
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let size = if i == 0 { 60. } else { 24. };
+                world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        format!("menu_{}", i),
+                        Anchor::Middle,
+                        Anchor::Middle,
+                        0.,
+                        180. - 40. * i as f32,
+                        1.,
+                        400.,
+                        60.,
+                    ))
+                    .with(UiText::new(
+                        font.clone(),
+                        line.to_string(),
+                        [1., 1., 1., 1.],
+                        size,
+                    ))
+                    .build()
+            })
+            .collect();
+    }
+
+    fn hide(&mut self, world: &mut World) {
+        if let Err(err) = world.delete_entities(&self.entities) {
+            warn!("Could not clean up menu: {}", err);
+        }
+        self.entities.clear();
+    }
+}
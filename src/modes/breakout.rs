@@ -0,0 +1,478 @@
+use amethyst::{
+    config::Config,
+    core::{timing::Time, transform::Transform},
+    ecs::prelude::{
+        Component, DenseVecStorage, DispatcherBuilder, Entities, Entity, Join, Read, ReadStorage,
+        System, SystemData, Write, WriteStorage,
+    },
+    prelude::*,
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+    shrev::{EventChannel, ReaderId},
+    ui::{Anchor, UiText, UiTransform},
+    utils::application_root_dir,
+};
+use serde::{Deserialize, Serialize};
+
+use log::{info, warn};
+
+use std::f32::consts::FRAC_PI_2;
+
+use super::GameMode;
+use crate::events::GameEvent;
+use crate::loading::GameAssets;
+use crate::physics::{Collider, Contact, Velocity};
+use crate::pong::{
+    initialise_camera, spawn_ball_at, Ball, Edge, Goal, MatchEntity, Orientation, Paddle, Side,
+    ARENA_HEIGHT, ARENA_WIDTH, BALL_VELOCITY_X, BALL_VELOCITY_Y, PADDLE_HEIGHT, PADDLE_WIDTH,
+};
+
+const SERVE_DELAY: f32 = 1.0;
+const PADDLE_Y: f32 = 6.0; // centre of the paddle above the bottom of the arena
+const BRICK_HEIGHT: f32 = 4.0;
+const BRICK_GAP: f32 = 0.5; // between neighbouring bricks
+const BRICKS_TOP: f32 = 10.0; // space between the top wall and the first row
+const POINTS_PER_HIT: u32 = 10; // a brick is worth this for every hit it takes
+
+// contents of config/breakout.ron
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreakoutConfig {
+    pub lives: u32,
+    pub levels: Vec<String>, // level files, relative to assets/, played in order
+}
+
+impl Default for BreakoutConfig {
+    fn default() -> Self {
+        BreakoutConfig {
+            lives: 3,
+            levels: Vec::new(),
+        }
+    }
+}
+
+// a level file, one string per row of bricks from the top,
+// each character is how many hits that brick takes, '.' or ' ' leaves a gap
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Level {
+    pub rows: Vec<String>,
+}
+
+// a brick, breaks once it's been hit `hits` times
+pub struct Brick {
+    pub hits: u32,
+    pub points: u32,
+}
+
+impl Component for Brick {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// progress through the current level, kept up to date by the BrickSystem
+#[derive(Debug, Default)]
+pub struct BreakoutScore {
+    pub points: u32,
+    pub bricks_left: u32,
+}
+
+// one paddle along the bottom, a wall of bricks at the top and the bottom edge as the only goal
+// uses the same balls, paddles, bounces and events as classic pong,
+// bricks are static colliders that the BrickSystem knocks out when a ball touches them
+pub struct Breakout {
+    config: BreakoutConfig,
+    level: usize,
+    lives: u32,
+    serve_timer: Option<f32>,
+    cleared: bool, // every level done
+    hud: Option<Entity>,
+}
+
+impl Default for Breakout {
+    fn default() -> Self {
+        let config = match application_root_dir() {
+            Ok(root) => BreakoutConfig::load(root.join("config").join("breakout.ron")),
+            Err(err) => {
+                warn!("Could not find breakout config: {}", err);
+                BreakoutConfig::default()
+            }
+        };
+        Breakout {
+            lives: config.lives,
+            config,
+            level: 0,
+            serve_timer: None,
+            cleared: false,
+            hud: None,
+        }
+    }
+}
+
+impl GameMode for Breakout {
+    fn name(&self) -> &str {
+        "Breakout"
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        world.register::<Tint>();
+        *world.write_resource::<BreakoutScore>() = BreakoutScore::default();
+        self.level = 0;
+        self.lives = self.config.lives;
+        self.cleared = false;
+
+        initialise_arena(world);
+        initialise_camera(world);
+        self.hud = Some(initialise_hud(world));
+        if !self.load_level(world) {
+            self.cleared = true; // nothing to play
+        }
+    }
+
+    fn teardown(&mut self, world: &mut World) {
+        *world.write_resource::<BreakoutScore>() = BreakoutScore::default();
+        self.serve_timer = None;
+        self.hud = None;
+    }
+
+    fn systems(&self, dispatcher: &mut DispatcherBuilder<'static, 'static>) {
+        dispatcher.add(BrickSystem::default(), "brick_system", &[]);
+    }
+
+    fn update(&mut self, world: &mut World) {
+        if let Some(mut timer) = self.serve_timer.take() {
+            timer -= world.read_resource::<Time>().delta_seconds();
+            if timer <= 0.0 {
+                serve(world);
+            } else {
+                self.serve_timer.replace(timer);
+            }
+        }
+
+        let bricks_left = world.read_resource::<BreakoutScore>().bricks_left;
+        if bricks_left == 0 && self.winner(world).is_none() {
+            // level done, clear the ball away and move on
+            remove_balls(world);
+            self.level += 1;
+            if !self.load_level(world) {
+                self.cleared = true;
+                info!("Breakout: all levels cleared!");
+            }
+        }
+
+        self.update_hud(world);
+    }
+
+    fn on_goal(&mut self, world: &mut World, _scorer: Side) {
+        // the only goal is the bottom edge, the ball is lost
+        self.lives = self.lives.saturating_sub(1);
+        info!("Breakout: ball lost, {} lives left", self.lives);
+        if self.winner(world).is_none() {
+            self.serve_timer = Some(SERVE_DELAY);
+        }
+    }
+
+    // served again from the paddle, without losing a life
+    fn on_ball_removed(&mut self, world: &mut World) {
+        if self.winner(world).is_none() {
+            self.serve_timer = Some(SERVE_DELAY);
+        }
+    }
+
+    // clearing every level is a win for the player (left), running out of lives a loss
+    fn winner(&self, _world: &World) -> Option<Side> {
+        if self.cleared {
+            Some(Side::Left)
+        } else if self.lives == 0 {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+}
+
+impl Breakout {
+    // spawns the bricks of the current level and serves, false when there are no levels left
+    fn load_level(&mut self, world: &mut World) -> bool {
+        let path = match self.config.levels.get(self.level) {
+            Some(path) => path.clone(),
+            None => return false,
+        };
+        let level = match application_root_dir() {
+            Ok(root) => Level::load(root.join("assets").join(&path)),
+            Err(err) => {
+                warn!("Could not find level {}: {}", path, err);
+                Level::default()
+            }
+        };
+        let bricks = initialise_bricks(world, &level);
+        if bricks == 0 {
+            warn!("Level {} has no bricks, skipping it", path);
+        }
+        world.write_resource::<BreakoutScore>().bricks_left = bricks;
+        self.serve_timer = Some(SERVE_DELAY);
+        true
+    }
+
+    fn update_hud(&self, world: &mut World) {
+        let hud = match self.hud {
+            Some(hud) => hud,
+            None => return,
+        };
+        let text = match self.winner(world) {
+            Some(Side::Left) => "You win!".to_string(),
+            Some(Side::Right) => "Game over".to_string(),
+            None => format!(
+                "level {}   lives {}   score {}",
+                self.level + 1,
+                self.lives,
+                world.read_resource::<BreakoutScore>().points
+            ),
+        };
+        if let Some(ui_text) = world.write_storage::<UiText>().get_mut(hud) {
+            ui_text.text = text;
+        }
+    }
+}
+
+// walls on three sides, the bottom is open with a goal behind it, and the paddle along it
+fn initialise_arena(world: &mut World) {
+    let walls = vec![
+        Collider::segment([0.0, ARENA_HEIGHT], [0.0, 0.0]),
+        Collider::segment([ARENA_WIDTH, 0.0], [ARENA_WIDTH, ARENA_HEIGHT]),
+        Collider::segment([ARENA_WIDTH, ARENA_HEIGHT], [0.0, ARENA_HEIGHT]),
+    ];
+    for wall in walls {
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(wall)
+            .with(Transform::default())
+            .build();
+    }
+    world
+        .create_entity()
+        .with(MatchEntity)
+        .with(Goal::new(Side::Right, Edge::Bottom(0.0)))
+        .build();
+
+    // the usual paddle sprite turned on its side
+    let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(ARENA_WIDTH * 0.5, PADDLE_Y, 0.0);
+    transform.set_rotation_2d(FRAC_PI_2);
+    world
+        .create_entity()
+        .with(MatchEntity)
+        .with(SpriteRender {
+            sprite_sheet,
+            sprite_number: 0,
+        })
+        .with(Paddle::with_orientation(
+            Side::Left,
+            Orientation::Horizontal,
+        ))
+        .with(Collider::aabb(PADDLE_HEIGHT, PADDLE_WIDTH))
+        .with(Velocity::default())
+        .with(transform)
+        .build();
+}
+
+// lays the level's rows out across the full width of the arena, returns how many bricks there are
+fn initialise_bricks(world: &mut World, level: &Level) -> u32 {
+    let columns = level
+        .rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    if columns == 0 {
+        return 0;
+    }
+    let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+    let width = ARENA_WIDTH / columns as f32;
+    let mut count = 0;
+    for (row, line) in level.rows.iter().enumerate() {
+        for (column, cell) in line.chars().enumerate() {
+            let hits = match cell.to_digit(10) {
+                Some(hits) if hits > 0 => hits,
+                _ => continue, // gap
+            };
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(
+                (column as f32 + 0.5) * width,
+                ARENA_HEIGHT - BRICKS_TOP - (row as f32 + 0.5) * BRICK_HEIGHT,
+                0.0,
+            );
+            // the paddle sprite stretched to the brick's size
+            transform.set_scale(
+                [
+                    (width - BRICK_GAP) / PADDLE_WIDTH,
+                    (BRICK_HEIGHT - BRICK_GAP) / PADDLE_HEIGHT,
+                    1.0,
+                ]
+                .into(),
+            );
+            world
+                .create_entity()
+                .with(MatchEntity)
+                .with(SpriteRender {
+                    sprite_sheet: sprite_sheet.clone(),
+                    sprite_number: 0,
+                })
+                .with(Tint(brick_colour(hits)))
+                .with(Brick {
+                    hits,
+                    points: hits * POINTS_PER_HIT,
+                })
+                .with(Collider::aabb(width - BRICK_GAP, BRICK_HEIGHT - BRICK_GAP))
+                .with(transform)
+                .build();
+            count += 1;
+        }
+    }
+    count
+}
+
+fn initialise_hud(world: &mut World) -> Entity {
+    let font = world.read_resource::<GameAssets>().font.clone();
+    world
+        .create_entity()
+        .with(MatchEntity)
+        .with(UiTransform::new(
+            "breakout_hud".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0.,
+            -10.,
+            1.,
+            600.,
+            30.,
+        ))
+        .with(UiText::new(font, String::new(), [1., 1., 1., 1.], 25.))
+        .build()
+}
+
+// a fresh ball just above the paddle, heading up
+fn serve(world: &mut World) {
+    let paddle_x = {
+        let paddles = world.read_storage::<Paddle>();
+        let transforms = world.read_storage::<Transform>();
+        (&paddles, &transforms)
+            .join()
+            .map(|(_, transform)| transform.translation().x)
+            .next()
+            .unwrap_or(ARENA_WIDTH * 0.5)
+    };
+    let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+    spawn_ball_at(
+        world,
+        sprite_sheet,
+        [paddle_x, PADDLE_Y + PADDLE_WIDTH],
+        [BALL_VELOCITY_Y, BALL_VELOCITY_X],
+    );
+}
+
+fn remove_balls(world: &mut World) {
+    let balls = {
+        let entities = world.entities();
+        let balls = world.read_storage::<Ball>();
+        (&entities, &balls)
+            .join()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>()
+    };
+    if let Err(err) = world.delete_entities(&balls) {
+        warn!("Could not remove ball: {}", err);
+    }
+}
+
+// tougher bricks are warmer colours
+fn brick_colour(hits: u32) -> Srgba {
+    match hits {
+        1 => Srgba::new(0.3, 0.8, 1.0, 1.0),
+        2 => Srgba::new(0.4, 1.0, 0.4, 1.0),
+        3 => Srgba::new(1.0, 0.9, 0.3, 1.0),
+        _ => Srgba::new(1.0, 0.4, 0.3, 1.0),
+    }
+}
+
+// knocks bricks out when a ball touches them, the ball has already bounced off
+// (BounceSystem or rapier, both publish the Contact)
+#[derive(Default)]
+pub struct BrickSystem {
+    reader: Option<ReaderId<Contact>>,
+}
+
+impl<'s> System<'s> for BrickSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Ball>,
+        WriteStorage<'s, Brick>,
+        WriteStorage<'s, Tint>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Velocity>,
+        Read<'s, EventChannel<Contact>>,
+        Write<'s, BreakoutScore>,
+        Write<'s, EventChannel<GameEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            balls,
+            mut bricks,
+            mut tints,
+            transforms,
+            velocities,
+            contacts,
+            mut score,
+            mut events,
+        ): Self::SystemData,
+    ) {
+        let reader = match self.reader.as_mut() {
+            Some(reader) => reader,
+            None => return,
+        };
+        for contact in contacts.read(reader) {
+            let (ball, brick_entity) = if balls.contains(contact.a) {
+                (contact.a, contact.b)
+            } else if balls.contains(contact.b) {
+                (contact.b, contact.a)
+            } else {
+                continue;
+            };
+            let brick = match bricks.get_mut(brick_entity) {
+                Some(brick) if brick.hits > 0 => brick,
+                _ => continue,
+            };
+
+            brick.hits -= 1;
+            if brick.hits > 0 {
+                if let Some(tint) = tints.get_mut(brick_entity) {
+                    *tint = Tint(brick_colour(brick.hits));
+                }
+                continue;
+            }
+
+            score.points += brick.points;
+            score.bricks_left = score.bricks_left.saturating_sub(1);
+            let position = transforms
+                .get(brick_entity)
+                .map_or([0.0, 0.0], |transform| {
+                    [transform.translation().x, transform.translation().y]
+                });
+            events.single_write(GameEvent::BrickBreak {
+                position,
+                speed: velocities.get(ball).map_or(0.0, Velocity::speed),
+            });
+            if let Err(err) = entities.delete(brick_entity) {
+                warn!("Could not remove brick: {}", err);
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(world.fetch_mut::<EventChannel<Contact>>().register_reader());
+    }
+}
@@ -0,0 +1,181 @@
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::DispatcherBuilder,
+    input::{InputHandler, StringBindings},
+    prelude::*,
+};
+
+use log::info;
+
+use super::GameMode;
+use crate::ai::{initialise_ai, Difficulty};
+use crate::handicap::Handicaps;
+use crate::loading::GameAssets;
+use crate::pong::{
+    initialise_ability_hud, initialise_camera, initialise_goals, initialise_paddles,
+    initialise_scoreboard, initialise_walls, refresh_scoreboard, spawn_ball, ScoreText, Side,
+};
+use crate::profiles::{swap_keys, Players};
+use crate::rules::Rules;
+use crate::scoring::{MatchClock, ScoreBoard};
+use crate::settings::{load_user_settings, save_user_settings};
+use crate::stats::MatchStats;
+use crate::systems::MatchClockSystem;
+
+// seconds between a point and the next serve (and before the first one)
+const SERVE_DELAY: f32 = 1.0;
+
+// two paddles, two goals, scored by whatever the rules config says
+#[derive(Default)]
+pub struct Classic {
+    serve_timer: Option<f32>,
+    computers: Vec<(Side, Difficulty)>, // paddles the AiSystem plays
+    swapped_keys: bool,                 // the players asked for each other's keys
+}
+
+impl Classic {
+    // the paddle on `side` is played by the computer, see config/ai.ron
+    pub fn with_computer(mut self, side: Side, difficulty: Difficulty) -> Self {
+        self.computers.push((side, difficulty));
+        self
+    }
+}
+
+impl GameMode for Classic {
+    fn name(&self) -> &str {
+        "Classic"
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        *world.write_resource::<ScoreBoard>() = ScoreBoard::default();
+        let players = world
+            .try_fetch::<Players>()
+            .map(|players| players.clone())
+            .unwrap_or_default();
+        world.write_resource::<MatchStats>().players =
+            [players.left.name.clone(), players.right.name.clone()];
+        if players.wants_swapped_keys() {
+            swap_keys(
+                &mut world
+                    .write_resource::<InputHandler<StringBindings>>()
+                    .bindings,
+            );
+            self.swapped_keys = true;
+        }
+
+        let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+        initialise_paddles(world, sprite_sheet.clone(), &[Side::Left, Side::Right]);
+        for &(side, difficulty) in &self.computers {
+            initialise_ai(world, side, difficulty);
+        }
+        initialise_walls(world);
+        initialise_goals(world, sprite_sheet);
+        initialise_camera(world);
+        initialise_scoreboard(world);
+        initialise_ability_hud(world);
+        // a handicapped player may start a few points (or tennis games) up
+        {
+            let handicaps = world.read_resource::<Handicaps>().clone();
+            let rules = world.read_resource::<Rules>();
+            let mut scores = world.write_resource::<ScoreBoard>();
+            for &side in &[Side::Left, Side::Right] {
+                scores.head_start(side, handicaps.side(side).starting_score, &rules.scoring);
+            }
+        }
+        refresh_scoreboard(world);
+        self.serve_timer = Some(SERVE_DELAY);
+    }
+
+    fn teardown(&mut self, world: &mut World) {
+        if self.swapped_keys {
+            swap_keys(
+                &mut world
+                    .write_resource::<InputHandler<StringBindings>>()
+                    .bindings,
+            );
+            self.swapped_keys = false;
+        }
+        record_profiles(world);
+        world.remove::<ScoreText>();
+        *world.write_resource::<ScoreBoard>() = ScoreBoard::default();
+        self.serve_timer = None;
+    }
+
+    fn systems(&self, dispatcher: &mut DispatcherBuilder<'static, 'static>) {
+        dispatcher.add(MatchClockSystem, "match_clock_system", &[]);
+    }
+
+    fn update(&mut self, world: &mut World) {
+        if let Some(mut timer) = self.serve_timer.take() {
+            timer -= world.read_resource::<Time>().delta_seconds();
+            if timer <= 0.0 {
+                let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+                let server = world.read_resource::<ScoreBoard>().server;
+                spawn_ball(world, sprite_sheet, server);
+            } else {
+                self.serve_timer.replace(timer);
+            }
+        }
+    }
+
+    fn on_goal(&mut self, world: &mut World, scorer: Side) {
+        {
+            let rules = world.read_resource::<Rules>();
+            let mut scores = world.write_resource::<ScoreBoard>();
+            scores.point(scorer, &rules.scoring);
+            info!(
+                "Score: | {:^3} | {:^3} | {}",
+                scores.points_text(Side::Left, &rules.scoring),
+                scores.points_text(Side::Right, &rules.scoring),
+                scores.status_text(&rules.scoring)
+            );
+        }
+        refresh_scoreboard(world);
+        if self.winner(world).is_none() {
+            self.serve_timer = Some(SERVE_DELAY);
+        }
+    }
+
+    fn on_ball_removed(&mut self, world: &mut World) {
+        if self.winner(world).is_none() {
+            self.serve_timer = Some(SERVE_DELAY);
+        }
+    }
+
+    fn winner(&self, world: &World) -> Option<Side> {
+        // points decide the match, or the clock does when time runs out with someone ahead
+        world
+            .read_resource::<ScoreBoard>()
+            .winner
+            .or(world.read_resource::<MatchClock>().winner)
+    }
+}
+
+// adds a finished match to the stats and history of whichever players have a profile
+fn record_profiles(world: &World) {
+    let stats = world.read_resource::<MatchStats>();
+    if stats.winner.is_none() {
+        return; // abandoned or restarted
+    }
+    let players = match world.try_fetch::<Players>() {
+        Some(players) => players.clone(),
+        None => return,
+    };
+
+    let mut settings = load_user_settings();
+    let mut changed = false;
+    for &side in &[Side::Left, Side::Right] {
+        let name = match &players.side(side).profile {
+            Some(name) => name,
+            None => continue,
+        };
+        let opponent = &players.side(side.other()).name;
+        if let Some(profile) = settings.profiles.iter_mut().find(|p| &p.name == name) {
+            profile.record(side, opponent, &stats);
+            changed = true;
+        }
+    }
+    if changed {
+        save_user_settings(&settings);
+    }
+}
@@ -0,0 +1,363 @@
+use amethyst::{
+    config::Config,
+    core::{timing::Time, transform::Transform},
+    ecs::prelude::Entity,
+    prelude::*,
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+    ui::{Anchor, LineMode, UiText, UiTransform},
+    utils::application_root_dir,
+};
+use serde::{Deserialize, Serialize};
+
+use log::{info, warn};
+
+use super::GameMode;
+use crate::loading::GameAssets;
+use crate::pong::{
+    initialise_camera, initialise_paddles, initialise_walls, spawn_ball_at, Ball, Edge, Goal,
+    MatchEntity, Side, ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS,
+};
+
+// the drill the menu starts, relative to assets/
+pub const DEFAULT_DRILL: &str = "drills/basic.ron";
+
+const LAUNCHER_X: f32 = ARENA_WIDTH - 4.0;
+const START_DELAY: f32 = 1.5;
+
+// a drill file: patterns are fired one after the other at the left player
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrillFile {
+    pub patterns: Vec<Pattern>,
+}
+
+impl DrillFile {
+    // a pattern of no balls has nothing to fire or report on, it's left out with a warning
+    fn skip_empty_patterns(&mut self, file: &str) {
+        self.patterns.retain(|pattern| {
+            if pattern.count == 0 {
+                warn!(
+                    "Skipping pattern {} in {}, it has no balls",
+                    pattern.name, file
+                );
+            }
+            pattern.count > 0
+        });
+    }
+}
+
+// `count` balls fired the same way, one at a time, `interval` seconds after the last one was dealt with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pattern {
+    pub name: String,
+    pub count: u32,
+    pub interval: f32,
+    pub height: f32, // where the launcher fires from, 0 is the bottom of the arena
+    pub angle: f32,  // degrees above (positive) or below the horizontal
+    pub speed: f32,
+    pub spin: f32, // see SpinRules, only does anything if spin is enabled
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Pattern {
+            name: "straight".to_string(),
+            count: 5,
+            interval: 1.5,
+            height: ARENA_HEIGHT * 0.5,
+            angle: 0.0,
+            speed: 75.0,
+            spin: 0.0,
+        }
+    }
+}
+
+// how a pattern went
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    returned: u32,
+    missed: u32,
+}
+
+// practice against a launcher on the right: it fires the patterns from a drill file at the left
+// paddle, a ball that makes it back to the right edge is a return, one that gets past is a miss
+// at the end the accuracy for each pattern is shown and logged
+pub struct Drill {
+    file: String,
+    drill: DrillFile,
+    pattern: usize,
+    fired: u32, // balls fired in the current pattern
+    in_flight: bool,
+    launch_timer: Option<f32>,
+    tallies: Vec<Tally>,
+    launcher: Option<Entity>,
+    hud: Option<Entity>,
+}
+
+impl Drill {
+    pub fn new(file: &str) -> Self {
+        Drill {
+            file: file.to_string(),
+            drill: DrillFile::default(),
+            pattern: 0,
+            fired: 0,
+            in_flight: false,
+            launch_timer: None,
+            tallies: Vec::new(),
+            launcher: None,
+            hud: None,
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.pattern >= self.drill.patterns.len()
+    }
+
+    // per pattern accuracy, one line each
+    fn report(&self) -> String {
+        self.drill
+            .patterns
+            .iter()
+            .zip(&self.tallies)
+            .map(|(pattern, tally)| {
+                let total = tally.returned + tally.missed;
+                let accuracy = if total > 0 {
+                    tally.returned as f32 * 100.0 / total as f32
+                } else {
+                    0.0
+                };
+                format!(
+                    "{}: {}/{} returned ({:.0}%)",
+                    pattern.name, tally.returned, total, accuracy
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn launch(&mut self, world: &mut World) {
+        let pattern = match self.drill.patterns.get(self.pattern) {
+            Some(pattern) if pattern.count > 0 => pattern,
+            _ => return,
+        };
+        let angle = pattern.angle.to_radians();
+        let position = [
+            LAUNCHER_X - BALL_RADIUS * 2.0,
+            pattern
+                .height
+                .max(BALL_RADIUS * 2.0)
+                .min(ARENA_HEIGHT - BALL_RADIUS * 2.0),
+        ];
+        let velocity = [-pattern.speed * angle.cos(), pattern.speed * angle.sin()];
+        let spin = pattern.spin;
+
+        let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+        let ball = spawn_ball_at(world, sprite_sheet, position, velocity);
+        if let Some(ball) = world.write_storage::<Ball>().get_mut(ball) {
+            ball.spin = spin;
+        }
+        self.fired += 1;
+        self.in_flight = true;
+    }
+
+    // puts the launcher where the current pattern fires from
+    fn move_launcher(&self, world: &mut World) {
+        let height = match (self.drill.patterns.get(self.pattern), self.launcher) {
+            (Some(pattern), Some(_)) => pattern.height,
+            _ => return,
+        };
+        if let Some(transform) = self
+            .launcher
+            .and_then(|launcher| world.write_storage::<Transform>().get_mut(launcher))
+        {
+            transform.set_translation_y(height);
+        }
+    }
+
+    fn update_hud(&self, world: &mut World) {
+        let text = match self.drill.patterns.get(self.pattern) {
+            Some(pattern) => {
+                let tally = self.tallies[self.pattern];
+                format!(
+                    "{}   ball {}/{}   returned {}   missed {}",
+                    pattern.name,
+                    self.fired.min(pattern.count),
+                    pattern.count,
+                    tally.returned,
+                    tally.missed
+                )
+            }
+            None => format!("Drill complete\n{}", self.report()),
+        };
+        if let Some(hud) = self
+            .hud
+            .and_then(|hud| world.write_storage::<UiText>().get_mut(hud))
+        {
+            hud.text = text;
+        }
+    }
+}
+
+impl GameMode for Drill {
+    fn name(&self) -> &str {
+        "Drill"
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.drill = match application_root_dir() {
+            Ok(root) => DrillFile::load(root.join("assets").join(&self.file)),
+            Err(err) => {
+                warn!("Could not find drill {}: {}", self.file, err);
+                DrillFile::default()
+            }
+        };
+        self.drill.skip_empty_patterns(&self.file);
+        self.pattern = 0;
+        self.fired = 0;
+        self.in_flight = false;
+        self.tallies = vec![Tally::default(); self.drill.patterns.len()];
+        self.launch_timer = Some(START_DELAY);
+
+        let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+        initialise_paddles(world, sprite_sheet.clone(), &[Side::Left]);
+        initialise_walls(world);
+        initialise_camera(world);
+        // past the player is a miss, back to the launcher is a return
+        let goals = vec![
+            Goal::new(Side::Right, Edge::Left(0.0)),
+            Goal::new(Side::Left, Edge::Right(ARENA_WIDTH)),
+        ];
+        for goal in goals {
+            world.create_entity().with(MatchEntity).with(goal).build();
+        }
+
+        // the launcher is just for show, it has no collider
+        world.register::<Tint>();
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(LAUNCHER_X, ARENA_HEIGHT * 0.5, 0.0);
+        self.launcher = Some(
+            world
+                .create_entity()
+                .with(MatchEntity)
+                .with(SpriteRender {
+                    sprite_sheet,
+                    sprite_number: 0,
+                })
+                .with(Tint(Srgba::new(1.0, 0.6, 0.2, 1.0)))
+                .with(transform)
+                .build(),
+        );
+        self.hud = Some(initialise_hud(world));
+        self.move_launcher(world);
+    }
+
+    fn teardown(&mut self, _world: &mut World) {
+        self.launch_timer = None;
+        self.launcher = None;
+        self.hud = None;
+    }
+
+    fn update(&mut self, world: &mut World) {
+        if let Some(mut timer) = self.launch_timer.take() {
+            timer -= world.read_resource::<Time>().delta_seconds();
+            if timer > 0.0 {
+                self.launch_timer.replace(timer);
+            } else if !self.finished() {
+                self.launch(world);
+            }
+        }
+        self.update_hud(world);
+    }
+
+    fn on_goal(&mut self, world: &mut World, scorer: Side) {
+        if !self.in_flight || self.finished() {
+            return;
+        }
+        self.in_flight = false;
+        let tally = &mut self.tallies[self.pattern];
+        match scorer {
+            Side::Left => tally.returned += 1,
+            Side::Right => tally.missed += 1,
+        }
+
+        // next ball, or next pattern once this one has fired them all
+        if self.fired >= self.drill.patterns[self.pattern].count {
+            self.pattern += 1;
+            self.fired = 0;
+            self.move_launcher(world);
+        }
+        match self.drill.patterns.get(self.pattern) {
+            Some(pattern) => self.launch_timer = Some(pattern.interval),
+            None => info!("Drill complete\n{}", self.report()),
+        }
+    }
+
+    // the ball doesn't count either way, the launcher fires it again
+    fn on_ball_removed(&mut self, _world: &mut World) {
+        if !self.in_flight || self.finished() {
+            return;
+        }
+        self.in_flight = false;
+        self.fired -= 1;
+        self.launch_timer = Some(self.drill.patterns[self.pattern].interval);
+    }
+
+    // a drill isn't won or lost, it's over once every pattern has been fired (the player's side)
+    fn winner(&self, _world: &World) -> Option<Side> {
+        if self.finished() {
+            Some(Side::Left)
+        } else {
+            None
+        }
+    }
+}
+
+fn initialise_hud(world: &mut World) -> Entity {
+    let font = world.read_resource::<GameAssets>().font.clone();
+    let mut text = UiText::new(font, String::new(), [1., 1., 1., 1.], 20.);
+    text.line_mode = LineMode::Wrap; // the final report has a line per pattern
+    world
+        .create_entity()
+        .with(MatchEntity)
+        .with(UiTransform::new(
+            "drill_hud".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0.,
+            -10.,
+            1.,
+            700.,
+            200.,
+        ))
+        .with(text)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_patterns_are_skipped() {
+        let pattern = |name: &str, count: u32| Pattern {
+            name: name.to_string(),
+            count,
+            ..Pattern::default()
+        };
+        let mut drill = DrillFile {
+            patterns: vec![
+                pattern("lob", 3),
+                pattern("nothing", 0),
+                pattern("smash", 1),
+            ],
+        };
+        drill.skip_empty_patterns("drills/test.ron");
+        let names = drill
+            .patterns
+            .iter()
+            .map(|pattern| pattern.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["lob", "smash"]);
+    }
+}
@@ -0,0 +1,44 @@
+use amethyst::{ecs::prelude::DispatcherBuilder, prelude::*};
+
+use crate::pong::Side;
+
+pub use self::breakout::Breakout;
+mod breakout;
+
+pub use self::classic::Classic;
+mod classic;
+
+pub use self::drill::{Drill, DEFAULT_DRILL};
+mod drill;
+
+pub use self::squash::Squash;
+mod squash;
+
+// a way of playing, run by the Pong state
+// everything a mode spawns should carry MatchEntity, so the state can clear it away
+// when the match ends or restarts; resources it adds are removed again in `teardown`
+pub trait GameMode {
+    // shown in the menu and in the match report
+    fn name(&self) -> &str;
+
+    // spawns the arena, players, HUD... and resets whatever the mode keeps score with
+    fn setup(&mut self, world: &mut World);
+
+    // undoes `setup` for anything that isn't an entity
+    fn teardown(&mut self, _world: &mut World) {}
+
+    // systems only this mode needs, run after the main dispatcher every frame while it's active
+    fn systems(&self, _dispatcher: &mut DispatcherBuilder<'static, 'static>) {}
+
+    // once a frame, for serves, timers and anything else the mode drives itself
+    fn update(&mut self, _world: &mut World) {}
+
+    // a ball went into a goal and `scorer` gets the point
+    fn on_goal(&mut self, world: &mut World, scorer: Side);
+
+    // the watchdog took a broken ball away, nobody scores and the mode serves again its own way
+    fn on_ball_removed(&mut self, world: &mut World);
+
+    // who won, None while the match is still going
+    fn winner(&self, world: &World) -> Option<Side>;
+}
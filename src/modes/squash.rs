@@ -0,0 +1,148 @@
+use amethyst::{
+    core::{timing::Time, transform::Transform},
+    ecs::prelude::Entity,
+    prelude::*,
+    shrev::{EventChannel, ReaderId},
+    ui::{Anchor, UiText, UiTransform},
+};
+
+use log::info;
+
+use super::GameMode;
+use crate::events::GameEvent;
+use crate::loading::GameAssets;
+use crate::physics::Collider;
+use crate::pong::{
+    initialise_camera, initialise_paddles, initialise_walls, spawn_ball, Edge, Goal, MatchEntity,
+    Side, ARENA_HEIGHT, ARENA_WIDTH,
+};
+use crate::settings::{load_user_settings, save_user_settings};
+
+const SERVE_DELAY: f32 = 1.0;
+
+// practice on your own: the left paddle against a solid wall where the right goal used to be
+// counts returns in a row and keeps the best run in the user settings
+// never ends by itself, so it's also the quickest way to check paddle physics by hand
+#[derive(Default)]
+pub struct Squash {
+    returns: u32,
+    best: u32,
+    serve_timer: Option<f32>,
+    events: Option<ReaderId<GameEvent>>,
+    hud: Option<Entity>,
+}
+
+impl GameMode for Squash {
+    fn name(&self) -> &str {
+        "Squash"
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.returns = 0;
+        self.best = load_user_settings().high_scores.squash;
+        self.events = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+
+        let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+        initialise_paddles(world, sprite_sheet, &[Side::Left]);
+        initialise_walls(world);
+        // the far wall, facing back into the arena
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(Collider::segment(
+                [ARENA_WIDTH, 0.0],
+                [ARENA_WIDTH, ARENA_HEIGHT],
+            ))
+            .with(Transform::default())
+            .build();
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(Goal::new(Side::Right, Edge::Left(0.0)))
+            .build();
+        initialise_camera(world);
+        self.hud = Some(initialise_hud(world));
+        self.serve_timer = Some(SERVE_DELAY);
+    }
+
+    fn teardown(&mut self, _world: &mut World) {
+        self.serve_timer = None;
+        self.events = None;
+        self.hud = None;
+    }
+
+    fn update(&mut self, world: &mut World) {
+        if let Some(reader) = self.events.as_mut() {
+            let returns = world
+                .fetch::<EventChannel<GameEvent>>()
+                .read(reader)
+                .filter(|event| match event {
+                    GameEvent::PaddleBounce { .. } => true,
+                    _ => false,
+                })
+                .count() as u32;
+            self.returns += returns;
+        }
+
+        if let Some(mut timer) = self.serve_timer.take() {
+            timer -= world.read_resource::<Time>().delta_seconds();
+            if timer <= 0.0 {
+                let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+                spawn_ball(world, sprite_sheet, Side::Left); // towards the wall
+            } else {
+                self.serve_timer.replace(timer);
+            }
+        }
+
+        if let Some(hud) = self.hud {
+            if let Some(text) = world.write_storage::<UiText>().get_mut(hud) {
+                text.text = format!("returns {}   best {}", self.returns, self.best);
+            }
+        }
+    }
+
+    // missed it, the run is over
+    fn on_goal(&mut self, _world: &mut World, _scorer: Side) {
+        info!("Squash: {} returns in a row", self.returns);
+        if self.returns > self.best {
+            self.best = self.returns;
+            let mut settings = load_user_settings();
+            settings.high_scores.squash = self.best;
+            save_user_settings(&settings);
+        }
+        self.returns = 0;
+        self.serve_timer = Some(SERVE_DELAY);
+    }
+
+    // not the player's fault, the run goes on
+    fn on_ball_removed(&mut self, _world: &mut World) {
+        self.serve_timer = Some(SERVE_DELAY);
+    }
+
+    fn winner(&self, _world: &World) -> Option<Side> {
+        None
+    }
+}
+
+fn initialise_hud(world: &mut World) -> Entity {
+    let font = world.read_resource::<GameAssets>().font.clone();
+    world
+        .create_entity()
+        .with(MatchEntity)
+        .with(UiTransform::new(
+            "squash_hud".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0.,
+            -10.,
+            1.,
+            500.,
+            30.,
+        ))
+        .with(UiText::new(font, String::new(), [1., 1., 1., 1.], 25.))
+        .build()
+}
@@ -0,0 +1,243 @@
+use amethyst::{
+    assets::Handle,
+    core::transform::Transform,
+    ecs::prelude::{Component, NullStorage},
+    prelude::*,
+    renderer::{
+        palette::Srgba, resources::Tint, transparent::Transparent, SpriteRender, SpriteSheet,
+    },
+    ui::{Anchor, UiText, UiTransform},
+};
+use serde::{Deserialize, Serialize};
+
+use std::f32::consts::PI;
+
+use crate::loading::GameAssets;
+use crate::pong::{MatchEntity, PADDLE_HEIGHT, PADDLE_WIDTH};
+
+// forces acting on balls during a match, part of the rules so every arena or mode can bring its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    pub gravity: [f32; 2], // units per second², always on
+    pub wind: Option<Wind>,
+    pub zones: Vec<Zone>,
+    pub max_speed: f32, // balls are never pushed faster than this
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Modifiers {
+            gravity: [0.0, 0.0],
+            wind: None,
+            zones: Vec::new(),
+            max_speed: 200.0,
+        }
+    }
+}
+
+impl Modifiers {
+    pub fn is_empty(&self) -> bool {
+        self.gravity == [0.0, 0.0] && self.wind.is_none() && self.zones.is_empty()
+    }
+}
+
+// gusts come and go on a fixed cycle: a gust swells up and dies down again over `gust_seconds`,
+// then it's calm for `calm_seconds`, and every other gust blows the opposite way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wind {
+    pub force: [f32; 2], // at the peak of a gust, units per second²
+    pub gust_seconds: f32,
+    pub calm_seconds: f32,
+}
+
+impl Wind {
+    // the push `clock` seconds into the match, the same every time for the same clock
+    pub fn force_at(&self, clock: f32) -> [f32; 2] {
+        let cycle = self.gust_seconds + self.calm_seconds;
+        if cycle <= 0.0 || self.gust_seconds <= 0.0 {
+            return [0.0, 0.0];
+        }
+        let gust = (clock / cycle).floor();
+        let into_gust = clock - gust * cycle;
+        if into_gust >= self.gust_seconds {
+            return [0.0, 0.0];
+        }
+        let direction = if gust % 2.0 == 0.0 { 1.0 } else { -1.0 };
+        let strength = (PI * into_gust / self.gust_seconds).sin() * direction;
+        [self.force[0] * strength, self.force[1] * strength]
+    }
+}
+
+// rectangle in arena coordinates that does something to balls inside it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Zone {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub effect: ZoneEffect,
+}
+
+impl Zone {
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        point[0] >= self.min[0]
+            && point[0] <= self.max[0]
+            && point[1] >= self.min[1]
+            && point[1] <= self.max[1]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ZoneEffect {
+    Slow(f32),         // fraction of speed lost per second inside
+    Speed(f32),        // fraction of speed gained per second inside
+    Deflect([f32; 2]), // constant push, units per second²
+}
+
+impl ZoneEffect {
+    // how the zone is drawn, slow is blue, speed is red and deflect is green
+    fn colour(&self) -> Srgba {
+        match self {
+            ZoneEffect::Slow(_) => Srgba::new(0.2, 0.4, 1.0, 0.25),
+            ZoneEffect::Speed(_) => Srgba::new(1.0, 0.3, 0.2, 0.25),
+            ZoneEffect::Deflect(_) => Srgba::new(0.3, 1.0, 0.4, 0.25),
+        }
+    }
+}
+
+// how far into the match the wind is and what it's doing right now,
+// kept up to date by the MoveBallsSystem
+#[derive(Debug, Default)]
+pub struct WindState {
+    pub clock: f32,
+    pub force: [f32; 2],
+}
+
+// the text showing gravity and wind, updated by the ModifierIndicatorSystem
+#[derive(Default)]
+pub struct ModifierIndicator;
+
+impl Component for ModifierIndicator {
+    type Storage = NullStorage<Self>;
+}
+
+// draws the zones and puts up the gravity/wind readout, nothing at all for a plain match
+pub fn initialise_modifiers(
+    world: &mut World,
+    modifiers: &Modifiers,
+    sprite_sheet: Handle<SpriteSheet>,
+) {
+    world.register::<Tint>();
+    world.register::<Transparent>();
+    *world.write_resource::<WindState>() = WindState::default();
+
+    // the paddle sprite stretched over the zone and tinted see-through
+    for zone in &modifiers.zones {
+        let width = zone.max[0] - zone.min[0];
+        let height = zone.max[1] - zone.min[1];
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(
+            zone.min[0] + width * 0.5,
+            zone.min[1] + height * 0.5,
+            -0.5, // under the paddles and ball
+        );
+        transform.set_scale([width / PADDLE_WIDTH, height / PADDLE_HEIGHT, 1.0].into());
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(SpriteRender {
+                sprite_sheet: sprite_sheet.clone(),
+                sprite_number: 0,
+            })
+            .with(Tint(zone.effect.colour()))
+            .with(Transparent)
+            .with(transform)
+            .build();
+    }
+
+    if modifiers.gravity == [0.0, 0.0] && modifiers.wind.is_none() {
+        return;
+    }
+    let font = world.read_resource::<GameAssets>().font.clone();
+    world
+        .create_entity()
+        .with(MatchEntity)
+        .with(ModifierIndicator)
+        .with(UiTransform::new(
+            "modifiers".to_string(),
+            Anchor::BottomMiddle,
+            Anchor::BottomMiddle,
+            0.,
+            20.,
+            1.,
+            500.,
+            30.,
+        ))
+        .with(UiText::new(font, String::new(), [1., 1., 1., 0.6], 20.))
+        .build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIND: Wind = Wind {
+        force: [30.0, -10.0],
+        gust_seconds: 2.0,
+        calm_seconds: 3.0,
+    };
+
+    fn assert_force(actual: [f32; 2], expected: [f32; 2]) {
+        for axis in 0..2 {
+            assert!(
+                (actual[axis] - expected[axis]).abs() < 1e-4,
+                "force {:?}, expected {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn a_gust_swells_and_dies_down() {
+        assert_force(WIND.force_at(0.0), [0.0, 0.0]);
+        assert_force(WIND.force_at(1.0), [30.0, -10.0]);
+        let half = 0.5f32.sqrt();
+        assert_force(WIND.force_at(0.5), [30.0 * half, -10.0 * half]);
+    }
+
+    #[test]
+    fn it_is_calm_from_the_end_of_a_gust_to_the_next_one() {
+        assert_force(WIND.force_at(2.0), [0.0, 0.0]);
+        assert_force(WIND.force_at(3.5), [0.0, 0.0]);
+        assert_force(WIND.force_at(4.999), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn every_other_gust_blows_the_other_way() {
+        assert_force(WIND.force_at(6.0), [-30.0, 10.0]);
+        assert_force(WIND.force_at(11.0), [30.0, -10.0]);
+    }
+
+    #[test]
+    fn wind_without_gusts_never_blows() {
+        let still = Wind {
+            gust_seconds: 0.0,
+            ..WIND
+        };
+        assert_force(still.force_at(1.0), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn zones_include_their_edges() {
+        let zone = Zone {
+            min: [40.0, 30.0],
+            max: [60.0, 70.0],
+            effect: ZoneEffect::Slow(0.5),
+        };
+        assert!(zone.contains([40.0, 30.0]));
+        assert!(zone.contains([60.0, 70.0]));
+        assert!(zone.contains([50.0, 70.0]));
+        assert!(!zone.contains([39.99, 50.0]));
+        assert!(!zone.contains([50.0, 70.01]));
+    }
+}
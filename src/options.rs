@@ -0,0 +1,200 @@
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, UiText, UiTransform},
+};
+
+use log::warn;
+
+use crate::audio::{AudioMixer, Bus, BUSES};
+use crate::loading::GameAssets;
+use crate::settings::{load_user_settings, save_user_settings};
+
+const SLIDER_STEPS: usize = 10;
+const SELECTED_COLOR: [f32; 4] = [1., 1., 0., 1.];
+const NORMAL_COLOR: [f32; 4] = [1., 1., 1., 1.];
+
+// pushed on top of the game or the menu: pauses the match, lets the player change volumes
+// and saves them on the way out
+#[derive(Default)]
+pub struct OptionsMenu {
+    selected: usize,
+    rows: Vec<Entity>, // one line of text per bus, same order as BUSES
+    labels: Vec<Entity>,
+    previous_time_scale: f32,
+}
+
+impl SimpleState for OptionsMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        // stop the match underneath us, music runs on real time so it keeps playing
+        {
+            let mut time = world.write_resource::<Time>();
+            self.previous_time_scale = time.time_scale();
+            time.set_time_scale(0.0);
+        }
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        let title = world
+            .create_entity()
+            .with(UiTransform::new(
+                "options_title".to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                -20.,
+                2.,
+                400.,
+                50.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "OPTIONS".to_string(),
+                NORMAL_COLOR,
+                40.,
+            ))
+            .build();
+        let hint = world
+            .create_entity()
+            .with(UiTransform::new(
+                "options_hint".to_string(),
+                Anchor::BottomMiddle,
+                Anchor::BottomMiddle,
+                0.,
+                20.,
+                2.,
+                480.,
+                30.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "Up/Down select  Left/Right adjust  Enter mute  Esc back".to_string(),
+                NORMAL_COLOR,
+                16.,
+            ))
+            .build();
+        self.labels = vec![title, hint];
+
+        self.rows = BUSES
+            .iter()
+            .enumerate()
+            .map(|(i, bus)| {
+                world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        format!("options_{:?}", bus),
+                        Anchor::Middle,
+                        Anchor::Middle,
+                        0.,
+                        60. - 60. * i as f32,
+                        2.,
+                        480.,
+                        40.,
+                    ))
+                    .with(UiText::new(font.clone(), String::new(), NORMAL_COLOR, 24.))
+                    .build()
+            })
+            .collect();
+
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        world
+            .write_resource::<Time>()
+            .set_time_scale(self.previous_time_scale);
+
+        // keep anything else in the settings file as it was, only the audio part is ours
+        let mut settings = load_user_settings();
+        settings.audio = world.read_resource::<AudioMixer>().clone();
+        save_user_settings(&settings);
+
+        let entities = self
+            .rows
+            .drain(..)
+            .chain(self.labels.drain(..))
+            .collect::<Vec<_>>();
+        if let Err(err) = world.delete_entities(&entities) {
+            warn!("Could not clean up options screen: {}", err);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) {
+                return Trans::Quit;
+            }
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+
+            let bus = BUSES[self.selected];
+            let step = 1.0 / SLIDER_STEPS as f32;
+            if is_key_down(event, VirtualKeyCode::Up) {
+                self.selected = (self.selected + BUSES.len() - 1) % BUSES.len();
+            } else if is_key_down(event, VirtualKeyCode::Down) {
+                self.selected = (self.selected + 1) % BUSES.len();
+            } else if is_key_down(event, VirtualKeyCode::Left) {
+                // applied straight to the mixer, so the change is heard immediately
+                data.world.write_resource::<AudioMixer>().adjust(bus, -step);
+            } else if is_key_down(event, VirtualKeyCode::Right) {
+                data.world.write_resource::<AudioMixer>().adjust(bus, step);
+            } else if is_key_down(event, VirtualKeyCode::Return) {
+                data.world.write_resource::<AudioMixer>().toggle_mute(bus);
+            } else {
+                return Trans::None;
+            }
+            self.refresh(data.world);
+        }
+        Trans::None
+    }
+}
+
+impl OptionsMenu {
+    // redraws every slider from the current mixer values
+    fn refresh(&self, world: &mut World) {
+        let mixer = world.read_resource::<AudioMixer>().clone();
+        let mut texts = world.write_storage::<UiText>();
+        for (i, (bus, row)) in BUSES.iter().zip(&self.rows).enumerate() {
+            if let Some(text) = texts.get_mut(*row) {
+                text.text = slider_text(*bus, &mixer);
+                text.color = if i == self.selected {
+                    SELECTED_COLOR
+                } else {
+                    NORMAL_COLOR
+                };
+            }
+        }
+    }
+}
+
+// e.g. "Music  [###-------]  30%" or "Music  [###-------] muted"
+fn slider_text(bus: Bus, mixer: &AudioMixer) -> String {
+    let volume = mixer.volume(bus);
+    let filled = (volume * SLIDER_STEPS as f32).round() as usize;
+    let label = match bus {
+        Bus::Master => "Master",
+        Bus::Music => "Music",
+        Bus::Sfx => "Effects",
+    };
+    let value = if mixer.muted(bus) {
+        "muted".to_string()
+    } else {
+        format!("{:>3}%", (volume * 100.0).round() as i32)
+    };
+    format!(
+        "{:<7} [{}{}] {:>5}",
+        label,
+        "#".repeat(filled),
+        "-".repeat(SLIDER_STEPS - filled.min(SLIDER_STEPS)),
+        value
+    )
+}
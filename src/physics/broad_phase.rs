@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+
+use super::Shape;
+
+// big enough that a ball only ever touches a handful of cells
+pub const DEFAULT_CELL_SIZE: f32 = 8.0;
+
+// uniform grid over everything with a Collider, rebuilt every tick by the CollisionSystem
+// only things sharing a cell are handed to the exact (narrow phase) test
+pub struct BroadPhase {
+    pub cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    seen: HashSet<(usize, usize)>, // scratch space for `pairs`, kept to save allocating every tick
+}
+
+impl Default for BroadPhase {
+    fn default() -> Self {
+        BroadPhase::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl BroadPhase {
+    pub fn new(cell_size: f32) -> Self {
+        BroadPhase {
+            cell_size,
+            cells: HashMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        // the same cells tend to be used every tick, so the ones used last time keep their
+        // vectors, cells nothing has been in since the clear before are dropped
+        self.cells.retain(|_, cell| {
+            let used = !cell.is_empty();
+            cell.clear();
+            used
+        });
+    }
+
+    // adds item `index` to every cell its bounds overlap
+    pub fn insert(&mut self, index: usize, bounds: Bounds) {
+        let (min_x, min_y) = self.cell(bounds.min);
+        let (max_x, max_y) = self.cell(bounds.max);
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.cells
+                    .entry((x, y))
+                    .or_insert_with(Vec::new)
+                    .push(index);
+            }
+        }
+    }
+
+    // every pair of items sharing at least one cell, each reported once, lower index first
+    pub fn pairs(&mut self) -> Vec<(usize, usize)> {
+        let seen = &mut self.seen;
+        seen.clear();
+        let mut pairs = Vec::new();
+        for cell in self.cells.values() {
+            for (i, &a) in cell.iter().enumerate() {
+                for &b in &cell[i + 1..] {
+                    let pair = if a < b { (a, b) } else { (b, a) };
+                    if seen.insert(pair) {
+                        pairs.push(pair);
+                    }
+                }
+            }
+        }
+        // cell order is random, sort so contacts come out the same way every run
+        pairs.sort();
+        pairs
+    }
+
+    fn cell(&self, point: [f32; 2]) -> (i32, i32) {
+        (
+            (point[0] / self.cell_size).floor() as i32,
+            (point[1] / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+// axis aligned bounding box in world space
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl Bounds {
+    pub fn of(shape: &Shape, position: [f32; 2]) -> Bounds {
+        match *shape {
+            Shape::Circle { radius } => Bounds {
+                min: [position[0] - radius, position[1] - radius],
+                max: [position[0] + radius, position[1] + radius],
+            },
+            Shape::Aabb {
+                half_width,
+                half_height,
+            } => Bounds {
+                min: [position[0] - half_width, position[1] - half_height],
+                max: [position[0] + half_width, position[1] + half_height],
+            },
+            Shape::Segment { a, b } => Bounds {
+                min: [position[0] + a[0].min(b[0]), position[1] + a[1].min(b[1])],
+                max: [position[0] + a[0].max(b[0]), position[1] + a[1].max(b[1])],
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Bounds {
+        Bounds {
+            min: [x, y],
+            max: [x + size, y + size],
+        }
+    }
+
+    #[test]
+    fn bodies_sharing_several_cells_are_paired_once() {
+        let mut grid = BroadPhase::new(10.0);
+        // both cover the same four cells
+        grid.insert(0, square(5.0, 5.0, 10.0));
+        grid.insert(1, square(8.0, 8.0, 10.0));
+        assert_eq!(grid.pairs(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn bodies_far_apart_are_not_paired() {
+        let mut grid = BroadPhase::new(10.0);
+        grid.insert(0, square(0.0, 0.0, 5.0));
+        grid.insert(1, square(50.0, 50.0, 5.0));
+        grid.insert(2, square(-50.0, 0.0, 5.0));
+        assert!(grid.pairs().is_empty());
+    }
+
+    #[test]
+    fn pairs_come_out_lower_index_first_and_sorted() {
+        let mut grid = BroadPhase::new(10.0);
+        grid.insert(2, square(1.0, 1.0, 2.0));
+        grid.insert(0, square(3.0, 3.0, 2.0));
+        grid.insert(1, square(5.0, 5.0, 2.0));
+        assert_eq!(grid.pairs(), vec![(0, 1), (0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn clearing_forgets_bodies_and_drops_unused_cells() {
+        let mut grid = BroadPhase::new(10.0);
+        grid.insert(0, square(0.0, 0.0, 5.0));
+        grid.insert(1, square(2.0, 2.0, 5.0));
+        grid.clear();
+        assert!(grid.pairs().is_empty());
+
+        // the cell is still there for the next tick, and gone after a tick without it
+        assert_eq!(grid.cells.len(), 1);
+        grid.insert(0, square(100.0, 100.0, 5.0));
+        grid.clear();
+        assert_eq!(grid.cells.len(), 1);
+        assert!(grid.cells.contains_key(&(10, 10)));
+    }
+}
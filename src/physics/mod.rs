@@ -0,0 +1,118 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage, Entity};
+
+pub use self::broad_phase::{Bounds, BroadPhase};
+pub use self::narrow_phase::collide;
+mod broad_phase;
+mod narrow_phase;
+
+#[cfg(feature = "rapier")]
+pub use self::rapier::RapierSystemDesc;
+#[cfg(feature = "rapier")]
+mod rapier;
+
+// collision shape, positioned by the entity's Transform translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Circle { radius: f32 },
+    // axis aligned box, extents measured from the centre
+    Aabb { half_width: f32, half_height: f32 },
+    // one-sided line from `a` to `b` (relative to the transform), solid on its right hand side,
+    // things are pushed out towards the left hand side, e.g. into the arena for walls
+    Segment { a: [f32; 2], b: [f32; 2] },
+}
+
+pub struct Collider {
+    pub shape: Shape,
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Collider {
+            shape: Shape::Circle { radius },
+        }
+    }
+
+    pub fn aabb(width: f32, height: f32) -> Self {
+        Collider {
+            shape: Shape::Aabb {
+                half_width: width * 0.5,
+                half_height: height * 0.5,
+            },
+        }
+    }
+
+    pub fn segment(a: [f32; 2], b: [f32; 2]) -> Self {
+        Collider {
+            shape: Shape::Segment { a, b },
+        }
+    }
+
+    // how far the shape reaches from its centre vertically, handy for keeping things in the arena
+    pub fn half_height(&self) -> f32 {
+        match self.shape {
+            Shape::Circle { radius } => radius,
+            Shape::Aabb { half_height, .. } => half_height,
+            Shape::Segment { a, b } => (a[1] - b[1]).abs() * 0.5,
+        }
+    }
+
+    pub fn half_width(&self) -> f32 {
+        match self.shape {
+            Shape::Circle { radius } => radius,
+            Shape::Aabb { half_width, .. } => half_width,
+            Shape::Segment { a, b } => (a[0] - b[0]).abs() * 0.5,
+        }
+    }
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// units per second, anything with a Velocity is moved by the MoveBallsSystem
+// and is considered dynamic by the collision pass
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Velocity(pub [f32; 2]);
+
+impl Velocity {
+    pub fn speed(&self) -> f32 {
+        (self.0[0] * self.0[0] + self.0[1] * self.0[1]).sqrt()
+    }
+}
+
+impl Component for Velocity {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// two colliders overlapping this frame, written by the CollisionSystem (or the RapierSystem)
+// `normal` points from `b` towards `a` (the way `a` has to move to get out), `depth` is the overlap
+#[derive(Debug, Clone, Copy)]
+pub struct Contact {
+    pub a: Entity,
+    pub b: Entity,
+    pub normal: [f32; 2],
+    pub depth: f32,
+}
+
+impl Contact {
+    // the same contact seen from `b`'s side
+    pub fn flipped(&self) -> Contact {
+        Contact {
+            a: self.b,
+            b: self.a,
+            normal: [-self.normal[0], -self.normal[1]],
+            depth: self.depth,
+        }
+    }
+
+    // this contact from `entity`'s point of view, if it's involved at all
+    pub fn for_entity(&self, entity: Entity) -> Option<Contact> {
+        if self.a == entity {
+            Some(*self)
+        } else if self.b == entity {
+            Some(self.flipped())
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use super::Shape;
+
+// exact overlap test between two shapes at the given centres
+// returns the normal pointing from `b` towards `a` and the penetration depth
+pub fn collide(a: &Shape, pos_a: [f32; 2], b: &Shape, pos_b: [f32; 2]) -> Option<([f32; 2], f32)> {
+    match (*a, *b) {
+        (Shape::Circle { radius: ra }, Shape::Circle { radius: rb }) => {
+            circle_circle(pos_a, ra, pos_b, rb)
+        }
+        (
+            Shape::Circle { radius },
+            Shape::Aabb {
+                half_width,
+                half_height,
+            },
+        ) => circle_aabb(pos_a, radius, pos_b, [half_width, half_height]),
+        (Shape::Circle { radius }, Shape::Segment { a: sa, b: sb }) => {
+            circle_segment(pos_a, radius, add(pos_b, sa), add(pos_b, sb))
+        }
+        (
+            Shape::Aabb {
+                half_width: wa,
+                half_height: ha,
+            },
+            Shape::Aabb {
+                half_width: wb,
+                half_height: hb,
+            },
+        ) => aabb_aabb(pos_a, [wa, ha], pos_b, [wb, hb]),
+        // the other way around is the same test with the normal flipped
+        (Shape::Aabb { .. }, Shape::Circle { .. })
+        | (Shape::Segment { .. }, Shape::Circle { .. }) => {
+            collide(b, pos_b, a, pos_a).map(|(normal, depth)| ([-normal[0], -normal[1]], depth))
+        }
+        // walls don't collide with walls, boxes are kept in the arena by their own systems
+        _ => None,
+    }
+}
+
+fn circle_circle(pa: [f32; 2], ra: f32, pb: [f32; 2], rb: f32) -> Option<([f32; 2], f32)> {
+    let delta = sub(pa, pb);
+    let distance = length(delta);
+    let depth = ra + rb - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+    let normal = if distance > 0.0 {
+        scale(delta, 1.0 / distance)
+    } else {
+        [1.0, 0.0] // exactly on top of each other, any direction will do
+    };
+    Some((normal, depth))
+}
+
+fn circle_aabb(
+    centre: [f32; 2],
+    radius: f32,
+    box_centre: [f32; 2],
+    half: [f32; 2],
+) -> Option<([f32; 2], f32)> {
+    let min = sub(box_centre, half);
+    let max = add(box_centre, half);
+    let closest = [
+        centre[0].max(min[0]).min(max[0]),
+        centre[1].max(min[1]).min(max[1]),
+    ];
+    let delta = sub(centre, closest);
+    let distance = length(delta);
+
+    if distance > 0.0 {
+        // centre outside the box: push away from the closest point on its surface
+        let depth = radius - distance;
+        if depth <= 0.0 {
+            return None;
+        }
+        return Some((scale(delta, 1.0 / distance), depth));
+    }
+
+    // centre inside the box: leave through the nearest face
+    let faces = [
+        ([-1.0, 0.0], centre[0] - min[0]),
+        ([1.0, 0.0], max[0] - centre[0]),
+        ([0.0, -1.0], centre[1] - min[1]),
+        ([0.0, 1.0], max[1] - centre[1]),
+    ];
+    let (normal, inside) =
+        faces.iter().cloned().fold(
+            faces[0],
+            |best, face| if face.1 < best.1 { face } else { best },
+        );
+    Some((normal, radius + inside))
+}
+
+fn circle_segment(
+    centre: [f32; 2],
+    radius: f32,
+    a: [f32; 2],
+    b: [f32; 2],
+) -> Option<([f32; 2], f32)> {
+    let along = sub(b, a);
+    let segment_length = length(along);
+    if segment_length <= 0.0 {
+        return None;
+    }
+    // left hand side of a -> b is the open side
+    let face_normal = [-along[1] / segment_length, along[0] / segment_length];
+
+    let t = (dot(sub(centre, a), along) / (segment_length * segment_length))
+        .max(0.0)
+        .min(1.0);
+    let closest = add(a, scale(along, t));
+    let delta = sub(centre, closest);
+    let side = dot(delta, face_normal);
+
+    if side < 0.0 {
+        // behind the wall (e.g. tunnelled through in one big step), always push back out the front
+        if t <= 0.0 || t >= 1.0 {
+            return None; // went around the end, not through it
+        }
+        return Some((face_normal, radius - side));
+    }
+
+    let distance = length(delta);
+    let depth = radius - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+    let normal = if distance > 0.0 {
+        scale(delta, 1.0 / distance)
+    } else {
+        face_normal
+    };
+    Some((normal, depth))
+}
+
+fn aabb_aabb(
+    pa: [f32; 2],
+    half_a: [f32; 2],
+    pb: [f32; 2],
+    half_b: [f32; 2],
+) -> Option<([f32; 2], f32)> {
+    let delta = sub(pa, pb);
+    let overlap_x = half_a[0] + half_b[0] - delta[0].abs();
+    let overlap_y = half_a[1] + half_b[1] - delta[1].abs();
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return None;
+    }
+    // separate along whichever axis needs the smaller push
+    if overlap_x < overlap_y {
+        Some(([delta[0].signum(), 0.0], overlap_x))
+    } else {
+        Some(([0.0, delta[1].signum()], overlap_y))
+    }
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn scale(a: [f32; 2], s: f32) -> [f32; 2] {
+    [a[0] * s, a[1] * s]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn length(a: [f32; 2]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BALL: Shape = Shape::Circle { radius: 2.0 };
+    const BOX: Shape = Shape::Aabb {
+        half_width: 4.0,
+        half_height: 10.0,
+    };
+    // runs left to right, so the open side faces up
+    const FLOOR: Shape = Shape::Segment {
+        a: [-10.0, 0.0],
+        b: [10.0, 0.0],
+    };
+
+    // the ball at `position` against `other` sitting at the origin
+    fn ball(position: [f32; 2], other: &Shape) -> Option<([f32; 2], f32)> {
+        collide(&BALL, position, other, [0.0, 0.0])
+    }
+
+    fn assert_contact(contact: Option<([f32; 2], f32)>, normal: [f32; 2], depth: f32) {
+        let (actual_normal, actual_depth) = contact.expect("expected a contact");
+        for axis in 0..2 {
+            assert!(
+                (actual_normal[axis] - normal[axis]).abs() < 1e-5,
+                "normal {:?}, expected {:?}",
+                actual_normal,
+                normal
+            );
+        }
+        assert!(
+            (actual_depth - depth).abs() < 1e-5,
+            "depth {}, expected {}",
+            actual_depth,
+            depth
+        );
+    }
+
+    #[test]
+    fn circles_push_apart_along_the_line_between_them() {
+        let contact = collide(&BALL, [3.0, 0.0], &BALL, [0.0, 0.0]);
+        assert_contact(contact, [1.0, 0.0], 1.0);
+        assert!(collide(&BALL, [4.5, 0.0], &BALL, [0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn circle_against_a_box_face_points_out_of_the_face() {
+        assert_contact(ball([5.0, 0.0], &BOX), [1.0, 0.0], 1.0);
+        assert_contact(ball([0.0, -11.5], &BOX), [0.0, -1.0], 0.5);
+        assert!(ball([6.5, 0.0], &BOX).is_none());
+    }
+
+    #[test]
+    fn circle_against_a_box_corner_points_away_from_the_corner() {
+        let contact = ball([5.0, 11.0], &BOX);
+        let diagonal = 0.5f32.sqrt();
+        assert_contact(contact, [diagonal, diagonal], 2.0 - 2.0f32.sqrt());
+        assert!(ball([6.0, 12.0], &BOX).is_none());
+    }
+
+    #[test]
+    fn circle_centre_inside_a_box_leaves_through_the_nearest_face() {
+        // 1 from the right face, 7 from the left, 10 from the top and bottom
+        assert_contact(ball([3.0, 0.0], &BOX), [1.0, 0.0], 3.0);
+        assert_contact(ball([0.0, -8.0], &BOX), [0.0, -1.0], 4.0);
+    }
+
+    #[test]
+    fn box_against_circle_flips_the_normal() {
+        assert_contact(
+            collide(&BOX, [0.0, 0.0], &BALL, [5.0, 0.0]),
+            [-1.0, 0.0],
+            1.0,
+        );
+    }
+
+    #[test]
+    fn circle_on_the_open_side_of_a_segment_is_pushed_away_from_it() {
+        assert_contact(ball([0.0, 1.5], &FLOOR), [0.0, 1.0], 0.5);
+        // the segment's position moves it with its entity
+        assert_contact(
+            collide(&BALL, [0.0, 6.5], &FLOOR, [0.0, 5.0]),
+            [0.0, 1.0],
+            0.5,
+        );
+        assert!(ball([0.0, 2.5], &FLOOR).is_none());
+    }
+
+    #[test]
+    fn circle_past_the_end_of_a_segment_is_pushed_away_from_the_end() {
+        let contact = ball([11.0, 1.0], &FLOOR);
+        let diagonal = 0.5f32.sqrt();
+        assert_contact(contact, [diagonal, diagonal], 2.0 - 2.0f32.sqrt());
+    }
+
+    #[test]
+    fn circle_behind_a_segment_is_pushed_back_out_the_front() {
+        assert_contact(ball([0.0, -1.0], &FLOOR), [0.0, 1.0], 3.0);
+        // tunnelled right through in one step
+        assert_contact(ball([0.0, -5.0], &FLOOR), [0.0, 1.0], 7.0);
+    }
+
+    #[test]
+    fn circle_behind_a_segment_but_beyond_its_ends_is_left_alone() {
+        assert!(ball([12.0, -1.0], &FLOOR).is_none());
+        assert!(ball([-12.0, -1.0], &FLOOR).is_none());
+    }
+
+    #[test]
+    fn segment_against_circle_flips_the_normal() {
+        assert_contact(
+            collide(&FLOOR, [0.0, 0.0], &BALL, [0.0, 1.5]),
+            [0.0, -1.0],
+            0.5,
+        );
+    }
+
+    #[test]
+    fn boxes_separate_along_the_smaller_overlap() {
+        let small = Shape::Aabb {
+            half_width: 1.0,
+            half_height: 1.0,
+        };
+        assert_contact(
+            collide(&small, [4.5, 2.0], &BOX, [0.0, 0.0]),
+            [1.0, 0.0],
+            0.5,
+        );
+        assert_contact(
+            collide(&small, [0.0, -10.5], &BOX, [0.0, 0.0]),
+            [0.0, -1.0],
+            0.5,
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use amethyst::{
+    core::{timing::Time, SystemDesc, Transform},
+    ecs::prelude::{
+        Entities, Entity, Join, Read, ReadStorage, System, SystemData, World, Write, WriteStorage,
+    },
+    shrev::EventChannel,
+};
+
+use rapier2d::crossbeam::channel::{unbounded, Receiver};
+use rapier2d::prelude::*;
+
+use std::collections::HashMap;
+
+use super::{Collider, Contact, Shape, Velocity};
+use crate::events::GameEvent;
+use crate::modifiers::WindState;
+use crate::pong::{Ball, Paddle};
+use crate::rules::Rules;
+use crate::systems::{apply_hit, steer_ball, update_wind};
+
+#[derive(Default)]
+pub struct RapierSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, RapierSystem> for RapierSystemDesc {
+    fn build(self, world: &mut World) -> RapierSystem {
+        <RapierSystem as System<'_>>::SystemData::setup(world);
+        let (intersection_send, _intersections) = unbounded();
+        let (contact_send, contacts) = unbounded();
+        RapierSystem {
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            joints: JointSet::new(),
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            ccd: CCDSolver::new(),
+            events: ChannelEventCollector::new(intersection_send, contact_send),
+            contacts,
+            handles: HashMap::new(),
+            owners: HashMap::new(),
+        }
+    }
+}
+
+// what rapier knows about one of our entities
+struct Body {
+    body: RigidBodyHandle,
+    shape: Shape, // to notice when the Collider changes and the body needs rebuilding
+}
+
+// replaces the built-in integrate / collide / bounce systems when the `rapier` feature is on
+// Transform and Velocity stay the source of truth: they're pushed into rapier before
+// every step and read back after, so the rest of the game doesn't know the difference
+// - entities with a Collider but no Velocity are fixed (walls, static obstacles)
+// - paddles are kinematic, moved by the velocity the PaddleSystem gives them
+// - everything else with a Velocity (balls, moving obstacles) is fully simulated
+// rapier only does the bouncing, gravity, wind, zones, spin and smashes go through the same
+// functions the built-in systems use, on the velocity before it's handed over and after a hit
+// every hit is also published as a Contact, like the CollisionSystem does, for the systems
+// that react to touches rather than bounces (Breakout's bricks)
+pub struct RapierSystem {
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    joints: JointSet,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd: CCDSolver,
+    events: ChannelEventCollector,
+    contacts: Receiver<ContactEvent>,
+    handles: HashMap<Entity, Body>,
+    owners: HashMap<ColliderHandle, Entity>,
+}
+
+impl<'s> System<'s> for RapierSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Collider>,
+        WriteStorage<'s, Paddle>,
+        WriteStorage<'s, Ball>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Transform>,
+        Read<'s, Time>,
+        Read<'s, Rules>,
+        Write<'s, WindState>,
+        Write<'s, EventChannel<Contact>>,
+        Write<'s, EventChannel<GameEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            colliders,
+            mut paddles,
+            mut balls,
+            mut velocities,
+            mut transforms,
+            time,
+            rules,
+            mut wind,
+            mut contacts,
+            mut game_events,
+        ): Self::SystemData,
+    ) {
+        let delta = time.delta_seconds();
+        if delta <= 0.0 {
+            return; // paused
+        }
+
+        self.remove_stale(&entities, &colliders);
+
+        update_wind(&mut wind, &rules.modifiers, delta);
+        for (ball, velocity, transform) in (&mut balls, &mut velocities, &transforms).join() {
+            let position = [transform.translation().x, transform.translation().y];
+            steer_ball(&rules, wind.force, ball, position, velocity, delta);
+        }
+
+        // push our state into rapier
+        for (entity, collider, transform) in (&entities, &colliders, &transforms).join() {
+            let position = vector![transform.translation().x, transform.translation().y];
+            let velocity = velocities.get(entity).map(|velocity| velocity.0);
+            let kinematic = paddles.contains(entity);
+            let handle = self.body_for(entity, collider, position, velocity, kinematic);
+            let body = &mut self.bodies[handle];
+            match velocity {
+                Some(v) if kinematic => {
+                    body.set_next_kinematic_translation(position + vector![v[0], v[1]] * delta);
+                }
+                Some(v) => {
+                    // gameplay may have moved or redirected it (serves, smashes...)
+                    body.set_translation(position, true);
+                    body.set_linvel(vector![v[0], v[1]], true);
+                }
+                None => {}
+            }
+        }
+
+        let parameters = IntegrationParameters {
+            dt: delta,
+            ..IntegrationParameters::default()
+        };
+        self.pipeline.step(
+            &vector![0.0, 0.0],
+            &parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joints,
+            &mut self.ccd,
+            &(),
+            &self.events,
+        );
+
+        // and read the results back
+        for (entity, transform) in (&entities, &mut transforms).join() {
+            let body = match self.handles.get(&entity) {
+                Some(body) => &self.bodies[body.body],
+                None => continue,
+            };
+            if body.is_static() {
+                continue;
+            }
+            let translation = body.translation();
+            transform.set_translation_x(translation.x);
+            transform.set_translation_y(translation.y);
+            if body.is_dynamic() {
+                if let Some(velocity) = velocities.get_mut(entity) {
+                    velocity.0 = [body.linvel().x, body.linvel().y];
+                }
+            }
+        }
+
+        // rapier already bounced everything, add spin and smashes on top
+        // and tell the audio (and friends) about it
+        while let Ok(event) = self.contacts.try_recv() {
+            if let ContactEvent::Started(first, second) = event {
+                let (ball, other, ball_collider, other_collider) =
+                    match (self.owners.get(&first), self.owners.get(&second)) {
+                        (Some(&a), Some(&b)) if balls.contains(a) => (a, b, first, second),
+                        (Some(&a), Some(&b)) if balls.contains(b) => (b, a, second, first),
+                        _ => continue,
+                    };
+                let contact = self.contact(ball, other, ball_collider, other_collider);
+                if let Some(contact) = contact {
+                    contacts.single_write(contact);
+                }
+                let other_velocity = velocities.get(other).map_or([0.0, 0.0], |v| v.0);
+                if let (Some(contact), Some(state), Some(velocity)) =
+                    (contact, balls.get_mut(ball), velocities.get_mut(ball))
+                {
+                    // picked up by the ball's rigid body next step
+                    apply_hit(
+                        &rules,
+                        state,
+                        paddles.get_mut(other),
+                        velocity,
+                        contact.normal,
+                        other_velocity,
+                    );
+                }
+                let position = match transforms.get(ball) {
+                    Some(transform) => [transform.translation().x, transform.translation().y],
+                    None => continue,
+                };
+                let speed = velocities.get(ball).map_or(0.0, Velocity::speed);
+                game_events.single_write(match paddles.get(other) {
+                    Some(paddle) => GameEvent::PaddleBounce {
+                        side: paddle.side,
+                        position,
+                        speed,
+                    },
+                    None => GameEvent::WallBounce { position, speed },
+                });
+            }
+        }
+    }
+}
+
+impl RapierSystem {
+    // a contact rapier has started as our own Contact: `a` is the ball, the normal points
+    // out of `other` towards it and the depth is how far the two overlap
+    fn contact(
+        &self,
+        ball: Entity,
+        other: Entity,
+        ball_collider: ColliderHandle,
+        other_collider: ColliderHandle,
+    ) -> Option<Contact> {
+        let pair = self
+            .narrow_phase
+            .contact_pair(ball_collider, other_collider)?;
+        let manifold = pair
+            .manifolds
+            .iter()
+            .find(|manifold| !manifold.points.is_empty())?;
+        // the manifold's normal points from the pair's first collider to its second
+        let normal = manifold.data.normal;
+        let normal = if pair.collider1 == ball_collider {
+            [-normal.x, -normal.y]
+        } else {
+            [normal.x, normal.y]
+        };
+        // rapier's distances are negative while the shapes overlap
+        let depth = manifold
+            .points
+            .iter()
+            .map(|point| -point.dist)
+            .fold(0.0, f32::max);
+        Some(Contact {
+            a: ball,
+            b: other,
+            normal,
+            depth,
+        })
+    }
+
+    // drops bodies whose entity is gone or no longer has a Collider (e.g. after a restart)
+    fn remove_stale(&mut self, entities: &Entities<'_>, colliders: &ReadStorage<'_, Collider>) {
+        let stale = self
+            .handles
+            .keys()
+            .filter(|entity| !entities.is_alive(**entity) || !colliders.contains(**entity))
+            .cloned()
+            .collect::<Vec<_>>();
+        for entity in stale {
+            self.remove(entity);
+        }
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(body) = self.handles.remove(&entity) {
+            self.bodies.remove(
+                body.body,
+                &mut self.islands,
+                &mut self.colliders,
+                &mut self.joints,
+            );
+            self.owners.retain(|_, owner| *owner != entity);
+        }
+    }
+
+    // finds the rapier body for an entity, creating (or recreating) it when needed
+    fn body_for(
+        &mut self,
+        entity: Entity,
+        collider: &Collider,
+        position: Vector<Real>,
+        velocity: Option<[f32; 2]>,
+        kinematic: bool,
+    ) -> RigidBodyHandle {
+        if let Some(body) = self.handles.get(&entity) {
+            if body.shape == collider.shape {
+                return body.body;
+            }
+        }
+        self.remove(entity);
+
+        let builder = match velocity {
+            None => RigidBodyBuilder::new_static(),
+            Some(_) if kinematic => RigidBodyBuilder::new_kinematic_position_based(),
+            // fast and small, so continuous collision detection to stop it tunnelling
+            Some(v) => RigidBodyBuilder::new_dynamic()
+                .linvel(vector![v[0], v[1]])
+                .ccd_enabled(true),
+        };
+        let body = self.bodies.insert(builder.translation(position).build());
+
+        let shape = match collider.shape {
+            Shape::Circle { radius } => ColliderBuilder::ball(radius),
+            Shape::Aabb {
+                half_width,
+                half_height,
+            } => ColliderBuilder::cuboid(half_width, half_height),
+            Shape::Segment { a, b } => {
+                ColliderBuilder::segment(point![a[0], a[1]], point![b[0], b[1]])
+            }
+        };
+        // perfectly elastic and frictionless, like the built-in bounce
+        let handle = self.colliders.insert_with_parent(
+            shape
+                .restitution(1.0)
+                .restitution_combine_rule(CoefficientCombineRule::Max)
+                .friction(0.0)
+                .active_events(ActiveEvents::CONTACT_EVENTS)
+                .build(),
+            body,
+            &mut self.bodies,
+        );
+
+        self.owners.insert(handle, entity);
+        self.handles.insert(
+            entity,
+            Body {
+                body,
+                shape: collider.shape,
+            },
+        );
+        body
+    }
+}
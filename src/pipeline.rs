@@ -0,0 +1,121 @@
+use amethyst::{core::SystemDesc, ecs::prelude::System, prelude::*};
+
+// the order a frame of gameplay runs in, every system belongs to exactly one phase
+// systems in a phase only start once everything in the earlier phases has finished,
+// systems inside the same phase may run in parallel, so they must not depend on each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Input,        // turning raw input into intentions
+    Control,      // paddles (players and AI) acting on those intentions
+    Integrate,    // moving things by their velocities
+    Collision,    // finding contacts
+    Response,     // resolving contacts, bounces
+    Scoring,      // points, serves, win conditions
+    Presentation, // audio, UI, anything that only reacts to the above
+}
+
+pub const PHASES: [Phase; 7] = [
+    Phase::Input,
+    Phase::Control,
+    Phase::Integrate,
+    Phase::Collision,
+    Phase::Response,
+    Phase::Scoring,
+    Phase::Presentation,
+];
+
+type AddSystem<'a, 'b> =
+    Box<dyn FnOnce(GameDataBuilder<'a, 'b>, &[&str]) -> GameDataBuilder<'a, 'b> + 'a>;
+
+struct Entry<'a, 'b> {
+    phase: Phase,
+    name: String,
+    add: AddSystem<'a, 'b>,
+}
+
+// collects systems by phase and wires the dependencies between phases when built,
+// so nobody has to remember which system names to list as dependencies
+pub struct Pipeline<'a, 'b> {
+    game_data: GameDataBuilder<'a, 'b>,
+    after: Vec<String>, // systems outside the pipeline (bundles) the first phase waits for
+    entries: Vec<Entry<'a, 'b>>,
+}
+
+impl<'a, 'b> Pipeline<'a, 'b> {
+    pub fn new(game_data: GameDataBuilder<'a, 'b>) -> Self {
+        Pipeline {
+            game_data,
+            after: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    // e.g. "input_system" from the InputBundle
+    pub fn after(mut self, dependencies: &[&str]) -> Self {
+        self.after
+            .extend(dependencies.iter().map(|name| name.to_string()));
+        self
+    }
+
+    pub fn with<S>(mut self, phase: Phase, system: S, name: &str) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        let system_name = name.to_string();
+        self.entries.push(Entry {
+            phase,
+            name: name.to_string(),
+            add: Box::new(move |game_data, dependencies| {
+                game_data.with(system, &system_name, dependencies)
+            }),
+        });
+        self
+    }
+
+    pub fn with_system_desc<SD, S>(mut self, phase: Phase, system_desc: SD, name: &str) -> Self
+    where
+        SD: SystemDesc<'a, 'b, S> + 'static,
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        let system_name = name.to_string();
+        self.entries.push(Entry {
+            phase,
+            name: name.to_string(),
+            add: Box::new(move |game_data, dependencies| {
+                game_data.with_system_desc(system_desc, system_name.as_str(), dependencies)
+            }),
+        });
+        self
+    }
+
+    // each system depends on every system of the closest earlier phase that has any,
+    // which (transitively) orders it after all earlier phases
+    pub fn build(self) -> GameDataBuilder<'a, 'b> {
+        let Pipeline {
+            mut game_data,
+            after,
+            mut entries,
+        } = self;
+
+        let mut previous = after;
+        for phase in PHASES.iter() {
+            let (current, rest): (Vec<_>, Vec<_>) =
+                entries.into_iter().partition(|entry| entry.phase == *phase);
+            entries = rest;
+            if current.is_empty() {
+                continue;
+            }
+
+            let names = current
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect::<Vec<_>>();
+            for entry in current {
+                let dependencies = previous.iter().map(String::as_str).collect::<Vec<_>>();
+                game_data = (entry.add)(game_data, &dependencies);
+            }
+            previous = names;
+        }
+        game_data
+    }
+}
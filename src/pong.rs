@@ -1,14 +1,31 @@
 use amethyst::{
-    assets::{AssetStorage, Handle, Loader},
-    audio::AudioBundle,
-    core::{timing::Time, transform::Transform},
-    ecs::prelude::{Component, DenseVecStorage, Entity},
+    assets::Handle,
+    core::{transform::Transform, ArcThreadPool},
+    ecs::prelude::{
+        Component, DenseVecStorage, Dispatcher, DispatcherBuilder, Entity, Join, NullStorage,
+    },
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
     prelude::*,
-    renderer::{Camera, ImageFormat, SpriteRender, SpriteSheet, SpriteSheetFormat, Texture},
-    ui::{Anchor, TtfFormat, UiText, UiTransform},
+    renderer::{palette::Srgba, resources::Tint, Camera, SpriteRender, SpriteSheet},
+    shrev::{EventChannel, ReaderId},
+    ui::{Anchor, UiText, UiTransform},
 };
 
-use crate::audio::initialise_audio;
+use crate::audio::{Music, Playlist};
+use crate::events::GameEvent;
+use crate::handicap::Handicaps;
+use crate::loading::GameAssets;
+use crate::modes::GameMode;
+use crate::modifiers::initialise_modifiers;
+use crate::options::OptionsMenu;
+use crate::physics::{Collider, Velocity};
+use crate::profiles::Players;
+use crate::rules::Rules;
+use crate::scoring::{MatchClock, ScoreBoard};
+use crate::stats::{save_match_stats, MatchStats};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 
 // dimensions of playable area
 pub const ARENA_HEIGHT: f32 = 100.0;
@@ -16,24 +33,22 @@ pub const ARENA_WIDTH: f32 = 100.0;
 
 pub const PADDLE_HEIGHT: f32 = 16.0;
 pub const PADDLE_WIDTH: f32 = 4.0;
+pub const PADDLE_SPEED: f32 = 72.0; // units per second, was 1.2 per frame at 60fps
 
 pub const BALL_VELOCITY_X: f32 = 75.0;
 pub const BALL_VELOCITY_Y: f32 = 50.0;
 pub const BALL_RADIUS: f32 = 2.0;
 
+// tags every entity the match creates, so they can all be removed when it ends or restarts
 #[derive(Default)]
-pub struct ScoreBoard {
-    pub score_left: i32,
-    pub score_right: i32,
+pub struct MatchEntity;
+
+impl Component for MatchEntity {
+    type Storage = NullStorage<Self>; // no data, just marks the entity
 }
 
-fn initialise_scoreboard(world: &mut World) {
-    let font = world.read_resource::<Loader>().load(
-        "font/square.ttf",
-        TtfFormat,
-        (),
-        &world.read_resource(),
-    );
+pub fn initialise_scoreboard(world: &mut World) {
+    let font = world.read_resource::<GameAssets>().font.clone();
     let p1_transform = UiTransform::new(
         "P1".to_string(),
         Anchor::TopMiddle,
@@ -57,6 +72,7 @@ fn initialise_scoreboard(world: &mut World) {
 
     let p1_score = world
         .create_entity()
+        .with(MatchEntity)
         .with(p1_transform)
         .with(UiText::new(
             font.clone(),     // font
@@ -68,6 +84,7 @@ fn initialise_scoreboard(world: &mut World) {
 
     let p2_score = world
         .create_entity()
+        .with(MatchEntity)
         .with(p2_transform)
         .with(UiText::new(
             font.clone(),
@@ -77,29 +94,202 @@ fn initialise_scoreboard(world: &mut World) {
         ))
         .build();
 
-    world.insert(ScoreText { p1_score, p2_score });
+    // whose score is whose, in their colour
+    let players = world
+        .try_fetch::<Players>()
+        .map(|players| players.clone())
+        .unwrap_or_default();
+    for &(side, x) in &[(Side::Left, -50.), (Side::Right, 50.)] {
+        let player = players.side(side);
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(UiTransform::new(
+                format!("{:?}_name", side),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                x,
+                -28.,
+                1.,
+                200.,
+                22.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                player.name.clone(),
+                player.color,
+                14.,
+            ))
+            .build();
+    }
+
+    // games, sets, serve and the result, whatever the scoring rules have to say
+    let status_text = {
+        let rules = world.read_resource::<Rules>();
+        world
+            .read_resource::<ScoreBoard>()
+            .status_text(&rules.scoring)
+    };
+    let status = world
+        .create_entity()
+        .with(MatchEntity)
+        .with(UiTransform::new(
+            "status".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0.,
+            -130.,
+            1.,
+            500.,
+            30.,
+        ))
+        .with(UiText::new(
+            font.clone(),
+            status_text,
+            [1., 1., 1., 1.],
+            20.,
+        ))
+        .build();
+
+    // countdown between the scores, timed matches only
+    let clock_text = {
+        let rules = world.read_resource::<Rules>();
+        rules
+            .time_limit
+            .as_ref()
+            .map(|_| world.read_resource::<MatchClock>().text())
+    };
+    let clock = clock_text.map(|clock_text| {
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(UiTransform::new(
+                "clock".to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                -10.,
+                1.,
+                200.,
+                30.,
+            ))
+            .with(UiText::new(font, clock_text, [1., 1., 0.6, 1.], 25.))
+            .build()
+    });
+
+    world.insert(ScoreText {
+        p1_score,
+        p2_score,
+        status,
+        clock,
+    });
+}
+
+// dash and smash cooldowns under each player's score, kept up to date by the AbilityHudSystem
+pub struct AbilityIndicator {
+    pub side: Side,
+}
+
+impl Component for AbilityIndicator {
+    type Storage = DenseVecStorage<Self>;
+}
+
+pub fn initialise_ability_hud(world: &mut World) {
+    let font = world.read_resource::<GameAssets>().font.clone();
+    for &(side, x) in &[(Side::Left, -120.), (Side::Right, 120.)] {
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(AbilityIndicator { side })
+            .with(UiTransform::new(
+                format!("{:?}_abilities", side),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                x,
+                -100.,
+                1.,
+                220.,
+                25.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                String::new(),
+                [1., 1., 1., 0.8],
+                16.,
+            ))
+            .build();
+    }
 }
 
 pub struct ScoreText {
     pub p1_score: Entity,
     pub p2_score: Entity,
+    pub status: Entity,
+    pub clock: Option<Entity>,
 }
 
-#[derive(Default)]
+// shows the ScoreBoard as it is now, after a point or anything else that changed it
+pub fn refresh_scoreboard(world: &World) {
+    let score_text = match world.try_fetch::<ScoreText>() {
+        Some(score_text) => score_text,
+        None => return,
+    };
+    let scores = world.read_resource::<ScoreBoard>();
+    let rules = world.read_resource::<Rules>();
+    let texts = [
+        (
+            score_text.p1_score,
+            scores.points_text(Side::Left, &rules.scoring),
+        ),
+        (
+            score_text.p2_score,
+            scores.points_text(Side::Right, &rules.scoring),
+        ),
+        (score_text.status, scores.status_text(&rules.scoring)),
+    ];
+    let mut ui_text = world.write_storage::<UiText>();
+    for (entity, value) in texts.iter().cloned() {
+        if let Some(text) = ui_text.get_mut(entity) {
+            text.text = value;
+        }
+    }
+}
+
+// runs a match of whatever GameMode it's given: the mode sets the match up, keeps score
+// and decides who wins, the state takes care of what every mode shares
+// (its extra systems, restarting, pausing and leaving)
 pub struct Pong {
-    ball_spawn_timer: Option<f32>,
-    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    mode: Box<dyn GameMode>,
+    dispatcher: Option<Dispatcher<'static, 'static>>, // the mode's own systems
+    events: Option<ReaderId<GameEvent>>,
+    finished: bool,
+    pop_when_finished: bool, // goes straight back to whoever pushed it once there's a winner
+}
+
+impl Pong {
+    pub fn new(mode: Box<dyn GameMode>) -> Self {
+        Pong {
+            mode,
+            dispatcher: None,
+            events: None,
+            finished: false,
+            pop_when_finished: false,
+        }
+    }
+
+    // for a match someone else keeps the result of, like a tournament bracket
+    pub fn popping_when_finished(mut self) -> Self {
+        self.pop_when_finished = true;
+        self
+    }
 }
-// needs own state since, multiple mutators need to use sprite_sheet_handle
-// and also we only want the timer to be used once
-// timer will count down to 0, and then be None
-// Default allows creating default empty state
 
-fn initialise_camera(world: &mut World) {
+pub fn initialise_camera(world: &mut World) {
     let mut transform = Transform::default();
     transform.set_translation_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5, 1.0); // x, y, z
     world
         .create_entity()
+        .with(MatchEntity)
         .with(Camera::standard_2d(ARENA_WIDTH, ARENA_HEIGHT)) // orthographic projection of size of arena
         .with(transform) // position in game world
         .build();
@@ -112,61 +302,229 @@ fn initialise_camera(world: &mut World) {
 impl SimpleState for Pong {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         let world = data.world;
-        // let sprite_sheet_handle = load_sprite_sheet(world);
-        //        world.register::<Paddle>(); // configures storage for specific entity, there's a better way to do this
-        // no longer needed since done in PaddleSystem, and registered via there
-        // world.register::<Ball>();
-        // initialise_ball(world, sprite_sheet_handle.clone());
-
-        // wait 1 second before spawning ball
-        self.ball_spawn_timer.replace(1.0);
-        // shared loader
-        self.sprite_sheet_handle.replace(load_sprite_sheet(world));
-        initialise_paddles(world, self.sprite_sheet_handle.clone().unwrap()); // need to unwrap the option
-        initialise_camera(world);
-        initialise_scoreboard(world);
-        initialise_audio(world);
+        // no system reads the tag, so its storage has to be registered by hand
+        world.register::<MatchEntity>();
+
+        let mut dispatcher =
+            DispatcherBuilder::new().with_pool((*world.read_resource::<ArcThreadPool>()).clone());
+        self.mode.systems(&mut dispatcher);
+        let mut dispatcher = dispatcher.build();
+        dispatcher.setup(world);
+        self.dispatcher = Some(dispatcher);
+        self.events = Some(
+            world
+                .fetch_mut::<EventChannel<GameEvent>>()
+                .register_reader(),
+        );
+
+        self.start_match(world);
+        world.write_resource::<Music>().current = Playlist::Match;
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.end_match(data.world);
+        self.dispatcher = None;
+        self.events = None;
     }
 
     fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
-        if let Some(mut timer) = self.ball_spawn_timer.take() {
-            /* .pop() */
-            {
-                let time = data.world.fetch::<Time>(); // fetch... implied time object?
-                timer -= time.delta_seconds();
+        let world = &mut *data.world;
+        if let Some(dispatcher) = self.dispatcher.as_mut() {
+            dispatcher.dispatch(world);
+        }
+
+        // goals become points however the mode counts them
+        for event in self.events(world) {
+            match event {
+                GameEvent::Score { side, .. } => self.mode.on_goal(world, side),
+                GameEvent::BallRemoved => self.mode.on_ball_removed(world),
+                _ => {}
             }
-            if timer <= 0.0 {
-                // when timer hits zero
-                initialise_ball(data.world, self.sprite_sheet_handle.clone().unwrap());
-            } else {
-                // if not expired yet, push() it back in
-                self.ball_spawn_timer.replace(timer);
+        }
+        self.mode.update(world);
+
+        if !self.finished {
+            if let Some(winner) = self.mode.winner(world) {
+                self.finished = true;
+                info!("{} match won by the {:?} player", self.mode.name(), winner);
+                world.write_resource::<MatchStats>().winner = Some(winner);
+                if self.pop_when_finished {
+                    return Trans::Pop;
+                }
             }
         }
         Trans::None // this allows transitioning out of state (for now its None)
     }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) {
+                return Trans::Quit;
+            }
+            // options screen goes on top of the match and pauses it until popped
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Push(Box::new(OptionsMenu::default()));
+            }
+            if is_key_down(event, VirtualKeyCode::R) {
+                self.end_match(data.world);
+                self.start_match(data.world);
+            }
+            // back to the menu underneath
+            if is_key_down(event, VirtualKeyCode::Q) {
+                return Trans::Pop;
+            }
+        }
+        Trans::None
+    }
+}
+
+// the clock and stats every match starts from, whichever mode it is
+// inserted rather than written, the clock only exists once Classic's systems have run
+fn reset_match(world: &mut World, mode: &str) {
+    let clock = MatchClock::new(world.read_resource::<Rules>().time_limit.as_ref());
+    world.insert(clock);
+    world.insert(MatchStats::new(mode));
+}
+
+impl Pong {
+    // spawns everything a fresh match needs, the mode decides what that is
+    fn start_match(&mut self, world: &mut World) {
+        // goals from a match that was just torn down don't count
+        self.events(world);
+        self.finished = false;
+        reset_match(world, self.mode.name());
+        self.mode.setup(world);
+        // gravity, wind and zones move every ball whatever the mode, so every mode shows them
+        let modifiers = world.read_resource::<Rules>().modifiers.clone();
+        let sprite_sheet = world.read_resource::<GameAssets>().sprite_sheet.clone();
+        initialise_modifiers(world, &modifiers, sprite_sheet);
+    }
+
+    // removes every tagged entity, then lets the mode clean up its resources
+    fn end_match(&mut self, world: &mut World) {
+        let entities = {
+            let entities = world.entities();
+            let tags = world.read_storage::<MatchEntity>();
+            (&entities, &tags)
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>()
+        };
+        if let Err(err) = world.delete_entities(&entities) {
+            warn!("Could not clean up match: {}", err);
+        }
+        self.mode.teardown(world);
+
+        let stats = world.read_resource::<MatchStats>().clone();
+        if !stats.is_empty() {
+            info!("{}", stats.summary());
+            save_match_stats(&stats);
+        }
+    }
+
+    // goals and removed balls since the last frame, the events the mode has to hear about
+    fn events(&mut self, world: &World) -> Vec<GameEvent> {
+        let reader = match self.events.as_mut() {
+            Some(reader) => reader,
+            None => return Vec::new(),
+        };
+        world
+            .fetch::<EventChannel<GameEvent>>()
+            .read(reader)
+            .filter(|event| match event {
+                GameEvent::Score { .. } | GameEvent::BallRemoved => true,
+                _ => false,
+            })
+            .cloned()
+            .collect()
+    }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Side {
     Left,
     Right,
 }
 
+impl Side {
+    pub fn other(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+// which way a paddle slides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Vertical,   // up and down along a side of the arena, like classic pong
+    Horizontal, // left and right along the bottom, like breakout
+}
+
+// size lives in the Collider, current movement in the Velocity
 pub struct Paddle {
-    pub side: Side,
-    pub width: f32,
-    pub height: f32,
+    pub side: Side, // whose controls and abilities it uses
+    pub orientation: Orientation,
+    pub speed: f32, // units per second when not dashing, PADDLE_SPEED unless handicapped
+    pub dash: Ability,
+    pub smash: Ability,
 }
 
 impl Paddle {
-    fn new(side: Side) -> Paddle {
+    pub fn new(side: Side) -> Paddle {
+        Paddle::with_orientation(side, Orientation::Vertical)
+    }
+
+    pub fn with_orientation(side: Side, orientation: Orientation) -> Paddle {
         Paddle {
             side,
-            width: PADDLE_WIDTH,
-            height: PADDLE_HEIGHT,
+            orientation,
+            speed: PADDLE_SPEED,
+            dash: Ability::default(),
+            smash: Ability::default(),
+        }
+    }
+}
+
+// a paddle move on a cooldown, timers count down to zero in seconds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ability {
+    pub active: f32,   // time left in effect
+    pub cooldown: f32, // time until it can be used again
+    pub held: bool,    // key was down last frame, so holding it doesn't retrigger
+}
+
+impl Ability {
+    pub fn is_active(&self) -> bool {
+        self.active > 0.0
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown <= 0.0
+    }
+
+    // fires on the frame the key goes down if it's off cooldown, returns whether it did
+    pub fn trigger(&mut self, down: bool, duration: f32, cooldown: f32) -> bool {
+        let pressed = down && !self.held;
+        self.held = down;
+        if pressed && self.is_ready() {
+            self.active = duration;
+            self.cooldown = cooldown;
+            true
+        } else {
+            false
         }
     }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.active = (self.active - delta).max(0.0);
+        self.cooldown = (self.cooldown - delta).max(0.0);
+    }
 }
 
 // this trait allows the class to be 'attached' to game entities
@@ -174,77 +532,207 @@ impl Component for Paddle {
     type Storage = DenseVecStorage<Self>; // variations for fast access, low mem usage, etc. https://slide-rs.github.io/specs/05_storages.html#densevecstorage
 }
 
-fn initialise_paddles(world: &mut World, sprite_sheet: Handle<SpriteSheet>) {
-    let mut left_transform = Transform::default();
-    let mut right_transform = Transform::default();
-
-    // correctly position paddles
-    let y = ARENA_HEIGHT / 2.0;
-    left_transform.set_translation_xyz(PADDLE_WIDTH * 0.5, y, 0.0);
-    right_transform.set_translation_xyz(ARENA_WIDTH - PADDLE_WIDTH * 0.5, y, 0.0);
+// one paddle for each of `sides`, both for a match, just one for practice
+// sized and sped up or down by the player's handicap, if one is set, and in the player's colour
+pub fn initialise_paddles(world: &mut World, sprite_sheet: Handle<SpriteSheet>, sides: &[Side]) {
     // assign sprites for paddles
     let sprite_render = SpriteRender {
         sprite_sheet: sprite_sheet.clone(),
         sprite_number: 0, // first sprite index
     };
+    let handicaps = world
+        .try_fetch::<Handicaps>()
+        .map(|handicaps| handicaps.clone())
+        .unwrap_or_default();
+    let players = world
+        .try_fetch::<Players>()
+        .map(|players| players.clone())
+        .unwrap_or_default();
+    world.register::<Tint>();
 
-    // left plank
-    world
-        .create_entity()
-        .with(sprite_render.clone())
-        .with(Paddle::new(Side::Left))
-        .with(left_transform)
-        .build();
+    for &side in sides {
+        let handicap = handicaps.side(side);
+        let color = players.side(side).color;
+        // correctly position paddles
+        let x = match side {
+            Side::Left => PADDLE_WIDTH * 0.5,
+            Side::Right => ARENA_WIDTH - PADDLE_WIDTH * 0.5,
+        };
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, ARENA_HEIGHT / 2.0, 0.0);
+        transform.set_scale([1.0, handicap.paddle_height, 1.0].into());
 
-    // right plank
-    world
-        .create_entity()
-        .with(sprite_render.clone())
-        .with(Paddle::new(Side::Right))
-        .with(right_transform)
-        .build();
+        let mut paddle = Paddle::new(side);
+        paddle.speed *= handicap.paddle_speed;
+
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(sprite_render.clone())
+            .with(paddle)
+            .with(Tint(Srgba::new(color[0], color[1], color[2], color[3])))
+            .with(Collider::aabb(
+                PADDLE_WIDTH,
+                PADDLE_HEIGHT * handicap.paddle_height,
+            ))
+            .with(Velocity::default())
+            .with(transform)
+            .build();
+    }
 }
 
-// returns reference, for others to lazy 'read'
-fn load_sprite_sheet(world: &mut World) -> Handle<SpriteSheet> {
-    // loads spritesheet needed for graphics
-    // texture contains pixel data
-    // 'texture_handle' is a cloneable ref to texture
-    let texture_handle = {
-        // sharable resource, loaded when app is built
-        let loader = world.read_resource::<Loader>();
-        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
-        // path, type,
-        loader.load(
-            "texture/pong_spritesheet.png",
-            ImageFormat::default(),
-            (),
-            &texture_storage,
-        )
-    };
+// top and bottom of the arena, one-sided segments facing inwards
+pub fn initialise_walls(world: &mut World) {
+    let walls = vec![
+        Collider::segment([0.0, 0.0], [ARENA_WIDTH, 0.0]),
+        Collider::segment([ARENA_WIDTH, ARENA_HEIGHT], [0.0, ARENA_HEIGHT]),
+    ];
+    for wall in walls {
+        world
+            .create_entity()
+            .with(MatchEntity)
+            .with(wall)
+            .with(Transform::default())
+            .build();
+    }
+}
+
+// a line the ball must not cross: once the ball is past it, `scorer` gets the point
+// the GoalSystem checks every ball against every goal
+pub struct Goal {
+    pub scorer: Side,
+    pub edge: Edge,
+    pub span: [f32; 2], // the part of the line that counts, as y (left/right) or x (top/bottom)
+}
+
+impl Goal {
+    // the whole length of the line
+    pub fn new(scorer: Side, edge: Edge) -> Goal {
+        Goal {
+            scorer,
+            edge,
+            span: [std::f32::NEG_INFINITY, std::f32::INFINITY],
+        }
+    }
+
+    pub fn reached(&self, position: [f32; 2], radius: f32) -> bool {
+        let along = match self.edge {
+            Edge::Left(_) | Edge::Right(_) => position[1],
+            Edge::Bottom(_) | Edge::Top(_) => position[0],
+        };
+        along >= self.span[0] && along <= self.span[1] && self.edge.reached(position, radius)
+    }
+}
+
+impl Component for Goal {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// where the goal line is, the value is its x (left/right) or y (top/bottom) coordinate
+#[derive(Debug, Clone, Copy)]
+pub enum Edge {
+    Left(f32),
+    Right(f32),
+    Bottom(f32),
+    Top(f32),
+}
+
+impl Edge {
+    // whether a ball of `radius` at `position` has reached the line
+    pub fn reached(&self, position: [f32; 2], radius: f32) -> bool {
+        match *self {
+            Edge::Left(x) => position[0] <= x + radius,
+            Edge::Right(x) => position[0] >= x - radius,
+            Edge::Bottom(y) => position[1] <= y + radius,
+            Edge::Top(y) => position[1] >= y - radius,
+        }
+    }
+}
+
+// behind each paddle, a ball reaching one scores for the other player
+// a goal narrowed by a handicap is centred on its side, with wall above and below it
+pub fn initialise_goals(world: &mut World, sprite_sheet: Handle<SpriteSheet>) {
+    let handicaps = world
+        .try_fetch::<Handicaps>()
+        .map(|handicaps| handicaps.clone())
+        .unwrap_or_default();
+    world.register::<Tint>();
+
+    for &(side, x, edge) in &[
+        (Side::Left, 0.0, Edge::Left(0.0)),
+        (Side::Right, ARENA_WIDTH, Edge::Right(ARENA_WIDTH)),
+    ] {
+        let half = ARENA_HEIGHT * 0.5 * handicaps.side(side).goal_width.max(0.0).min(1.0);
+        let (low, high) = (ARENA_HEIGHT * 0.5 - half, ARENA_HEIGHT * 0.5 + half);
+        let mut goal = Goal::new(side.other(), edge);
+        goal.span = [low, high];
+        world.create_entity().with(MatchEntity).with(goal).build();
 
-    let loader = world.read_resource::<Loader>();
-    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
-    loader.load(
-        "texture/pong_spritesheet.ron",
-        SpriteSheetFormat(texture_handle), // refernce to source data?
-        (),
-        &sprite_sheet_store, // place to store data in?
-    )
+        // one-sided segments facing into the arena, like the top and bottom walls
+        let walls = match side {
+            Side::Left => [([x, low], [x, 0.0]), ([x, ARENA_HEIGHT], [x, high])],
+            Side::Right => [([x, 0.0], [x, low]), ([x, high], [x, ARENA_HEIGHT])],
+        };
+        for &(a, b) in &walls {
+            let length = (a[1] - b[1]).abs();
+            if length <= 0.0 {
+                continue;
+            }
+            // drawn as a thin grey strip so players can see where the goal is
+            let middle = (a[1] + b[1]) * 0.5;
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(x, middle, 0.0);
+            transform.set_scale([0.5, length / PADDLE_HEIGHT, 1.0].into());
+            world
+                .create_entity()
+                .with(MatchEntity)
+                .with(Collider::segment(
+                    [0.0, a[1] - middle],
+                    [0.0, b[1] - middle],
+                ))
+                .with(SpriteRender {
+                    sprite_sheet: sprite_sheet.clone(),
+                    sprite_number: 0,
+                })
+                .with(Tint(Srgba::new(0.5, 0.5, 0.5, 1.0)))
+                .with(transform)
+                .build();
+        }
+    }
 }
 
+// marks an entity as a ball, shape and movement are in its Collider and Velocity
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Ball {
-    pub velocity: [f32; 2], // probably, type; length
-    pub radius: f32,
+    pub spin: f32, // radians per second, anti-clockwise, see SpinRules
 }
 
 impl Component for Ball {
     type Storage = DenseVecStorage<Self>;
 }
 
-fn initialise_ball(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>) {
+// puts a ball in the middle heading away from `server`
+pub fn spawn_ball(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>, server: Side) {
+    let direction = match server {
+        Side::Left => 1.0,
+        Side::Right => -1.0,
+    };
+    spawn_ball_at(
+        world,
+        sprite_sheet_handle,
+        [ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0],
+        [BALL_VELOCITY_X * direction, BALL_VELOCITY_Y],
+    );
+}
+
+pub fn spawn_ball_at(
+    world: &mut World,
+    sprite_sheet_handle: Handle<SpriteSheet>,
+    position: [f32; 2],
+    velocity: [f32; 2],
+) -> Entity {
     let mut local_transform = Transform::default();
-    local_transform.set_translation_xyz(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0, 0.0);
+    local_transform.set_translation_xyz(position[0], position[1], 0.0);
 
     let sprite_render = SpriteRender {
         sprite_sheet: sprite_sheet_handle,
@@ -253,11 +741,34 @@ fn initialise_ball(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>)
 
     world
         .create_entity()
+        .with(MatchEntity)
         .with(sprite_render)
-        .with(Ball {
-            radius: BALL_RADIUS,
-            velocity: [BALL_VELOCITY_X, BALL_VELOCITY_Y],
-        })
+        .with(Ball::default())
+        .with(Collider::circle(BALL_RADIUS))
+        .with(Velocity(velocity))
         .with(local_transform)
-        .build();
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::{ClockPhase, TieBreak, TimeLimit};
+
+    #[test]
+    fn a_timed_match_of_another_mode_starts_in_a_fresh_world() {
+        let mut world = World::new();
+        world.insert(Rules {
+            time_limit: Some(TimeLimit {
+                seconds: 90.0,
+                tie_break: TieBreak::SuddenDeath,
+            }),
+            ..Rules::default()
+        });
+        reset_match(&mut world, "Squash");
+        let clock = world.read_resource::<MatchClock>();
+        assert_eq!(clock.phase, ClockPhase::Regulation);
+        assert_eq!(clock.text(), "1:30");
+        assert_eq!(world.read_resource::<MatchStats>().mode, "Squash");
+    }
 }
@@ -0,0 +1,223 @@
+use amethyst::input::{Bindings, Button, StringBindings};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::handicap::Handicap;
+use crate::pong::Side;
+use crate::stats::MatchStats;
+
+// how many matches each profile remembers
+const HISTORY_LENGTH: usize = 20;
+
+// bindings that belong to each half of the keyboard, see config/bindings.ron
+const SWAPPED_AXES: [(&str, &str); 1] = [("left_paddle", "right_paddle")];
+const SWAPPED_ACTIONS: [(&str, &str); 2] =
+    [("left_dash", "right_dash"), ("left_smash", "right_smash")];
+
+// someone who plays regularly, saved with the user settings
+// new profiles are added by editing config/user_settings.ron
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub color: [f32; 4], // their name on the scoreboard and their paddle
+    pub keys: Keys,
+    pub handicap: Handicap, // picked up on the match setup screen
+    pub stats: ProfileStats,
+    pub history: Vec<MatchRecord>, // latest last
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::new("Player", [1., 1., 1., 1.], Keys::LeftHand)
+    }
+}
+
+impl Profile {
+    pub fn new(name: &str, color: [f32; 4], keys: Keys) -> Self {
+        Profile {
+            name: name.to_string(),
+            color,
+            keys,
+            handicap: Handicap::default(),
+            stats: ProfileStats::default(),
+            history: Vec::new(),
+        }
+    }
+
+    // adds a finished match played on `side`
+    pub fn record(&mut self, side: Side, opponent: &str, stats: &MatchStats) {
+        let (goals_for, goals_against) = (stats.goals(side), stats.goals(side.other()));
+        let won = stats.winner == Some(side);
+        self.stats.played += 1;
+        if won {
+            self.stats.won += 1;
+        }
+        self.stats.goals_for += goals_for;
+        self.stats.goals_against += goals_against;
+        let longest = stats.rallies.iter().max().copied().unwrap_or(0);
+        self.stats.longest_rally = self.stats.longest_rally.max(longest);
+
+        self.history.push(MatchRecord {
+            opponent: opponent.to_string(),
+            goals_for,
+            goals_against,
+            won,
+        });
+        let extra = self.history.len().saturating_sub(HISTORY_LENGTH);
+        self.history.drain(..extra);
+    }
+}
+
+pub fn default_profiles() -> Vec<Profile> {
+    vec![
+        Profile::new("Player 1", [1., 1., 1., 1.], Keys::LeftHand),
+        Profile::new("Player 2", [0.6, 0.8, 1., 1.], Keys::RightHand),
+    ]
+}
+
+// which half of the keyboard someone likes to play with:
+// W/S with D/A for abilities, or the arrow keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Keys {
+    LeftHand,
+    RightHand,
+}
+
+impl Keys {
+    pub fn other(self) -> Keys {
+        match self {
+            Keys::LeftHand => Keys::RightHand,
+            Keys::RightHand => Keys::LeftHand,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Keys::LeftHand => "W/S",
+            Keys::RightHand => "arrows",
+        }
+    }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Keys::LeftHand
+    }
+}
+
+// totals over every match a profile has finished
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileStats {
+    pub played: u32,
+    pub won: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    pub longest_rally: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchRecord {
+    pub opponent: String,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    pub won: bool,
+}
+
+// who is playing each side of the next match, set by whatever screen starts it
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub name: String,
+    pub color: [f32; 4],
+    pub keys: Keys,
+    pub profile: Option<String>, // name of the profile results are added to, None for guests
+}
+
+impl Player {
+    pub fn guest(name: &str, keys: Keys) -> Self {
+        Player {
+            name: name.to_string(),
+            color: [1., 1., 1., 1.],
+            keys,
+            profile: None,
+        }
+    }
+}
+
+impl From<&Profile> for Player {
+    fn from(profile: &Profile) -> Self {
+        Player {
+            name: profile.name.clone(),
+            color: profile.color,
+            keys: profile.keys,
+            profile: Some(profile.name.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Players {
+    pub left: Player,
+    pub right: Player,
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Players {
+            left: Player::guest("Player 1", Keys::LeftHand),
+            right: Player::guest("Player 2", Keys::RightHand),
+        }
+    }
+}
+
+impl Players {
+    pub fn side(&self, side: Side) -> &Player {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    // both would rather use the other half of the keyboard
+    pub fn wants_swapped_keys(&self) -> bool {
+        self.left.keys == Keys::RightHand && self.right.keys == Keys::LeftHand
+    }
+}
+
+// hands the left paddle's keys to the right paddle and the other way round, doing it again undoes it
+pub fn swap_keys(bindings: &mut Bindings<StringBindings>) {
+    for &(left, right) in SWAPPED_AXES.iter() {
+        let left_axis = bindings.remove_axis(left);
+        let right_axis = bindings.remove_axis(right);
+        for (id, axis) in vec![(left, right_axis), (right, left_axis)] {
+            if let Some(axis) = axis {
+                if let Err(err) = bindings.insert_axis(id.to_string(), axis) {
+                    warn!("Could not rebind {}: {}", id, err);
+                }
+            }
+        }
+    }
+    for &(left, right) in SWAPPED_ACTIONS.iter() {
+        let left_combos = take_action_bindings(bindings, left);
+        let right_combos = take_action_bindings(bindings, right);
+        for (id, combos) in vec![(left, right_combos), (right, left_combos)] {
+            for combo in combos {
+                if let Err(err) = bindings.insert_action_binding(id.to_string(), combo) {
+                    warn!("Could not rebind {}: {}", id, err);
+                }
+            }
+        }
+    }
+}
+
+fn take_action_bindings(bindings: &mut Bindings<StringBindings>, id: &str) -> Vec<Vec<Button>> {
+    let combos = bindings
+        .action_bindings(id)
+        .map(|combo| combo.to_vec())
+        .collect::<Vec<_>>();
+    for combo in &combos {
+        bindings.remove_action_binding(id, combo);
+    }
+    combos
+}
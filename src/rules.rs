@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::modifiers::Modifiers;
+use crate::scoring::{Scoring, TimeLimit};
+
+// contents of config/rules.ron, how the game plays as opposed to how it looks or sounds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub scoring: Scoring,
+    pub time_limit: Option<TimeLimit>, // None for an untimed match
+    pub spin: SpinRules,
+    pub abilities: AbilityRules,
+    pub modifiers: Modifiers,
+}
+
+// spin a paddle puts on the ball, which curves its flight and kicks it along walls
+// spin is in radians per second, positive is anti-clockwise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpinRules {
+    pub enabled: bool,
+    pub transfer: f32, // spin gained per unit/second the paddle moves along its face
+    pub max_spin: f32, // spin never goes beyond this either way
+    pub curve: f32,    // how fast the flight direction turns per unit of spin
+    pub decay: f32,    // fraction of spin lost per second in flight
+    pub wall_grip: f32, // sideways speed gained per unit of spin on a wall bounce
+    pub wall_loss: f32, // fraction of spin lost on a wall bounce
+}
+
+impl Default for SpinRules {
+    fn default() -> Self {
+        SpinRules {
+            enabled: true,
+            transfer: 0.02,
+            max_spin: 3.0,
+            curve: 0.3,
+            decay: 0.4,
+            wall_grip: 4.0,
+            wall_loss: 0.5,
+        }
+    }
+}
+
+// paddle dash and smash, all times in seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbilityRules {
+    pub dash_seconds: f32, // how long a dash lasts
+    pub dash_speed: f32,   // paddle speed multiplier while dashing
+    pub dash_cooldown: f32,
+    pub smash_window: f32, // how long before contact a smash can be pressed and still count
+    pub smash_boost: f32,  // ball speed multiplier on a smash
+    pub smash_cooldown: f32,
+}
+
+impl Default for AbilityRules {
+    fn default() -> Self {
+        AbilityRules {
+            dash_seconds: 0.15,
+            dash_speed: 3.0,
+            dash_cooldown: 2.0,
+            smash_window: 0.2,
+            smash_boost: 1.5,
+            smash_cooldown: 3.0,
+        }
+    }
+}
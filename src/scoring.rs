@@ -0,0 +1,675 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pong::Side;
+
+// which scoring rules a match is played under, part of the rules config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Scoring {
+    // a point per goal, first to `points` wins, 0 plays forever
+    Classic {
+        points: u32,
+    },
+    // games to `points` won by `win_by`, serve changes every `serve_every` points
+    // (every point once both are a point from the game), best of `best_of` games
+    TableTennis {
+        points: u32,
+        win_by: u32,
+        serve_every: u32,
+        best_of: u32,
+    },
+    // 15/30/40 with deuce and advantage, sets of `games` won by two, best of `best_of` sets,
+    // serve changes every game
+    // there's no tie-break, every set is an advantage set and goes on until someone is two up
+    Tennis {
+        games: u32,
+        best_of: u32,
+    },
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Scoring::Classic { points: 0 }
+    }
+}
+
+// one player's side of the scoreboard
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tally {
+    pub points: u32, // in the current game
+    pub games: u32,  // in the current set (table tennis: in the match)
+    pub sets: u32,
+}
+
+pub struct ScoreBoard {
+    pub left: Tally,
+    pub right: Tally,
+    pub server: Side,
+    pub games_played: u32, // over the whole match, decides who serves first in a game
+    pub sudden_death: bool, // the next point wins the match, set when time runs out on a tie
+    pub winner: Option<Side>,
+}
+
+impl Default for ScoreBoard {
+    fn default() -> Self {
+        ScoreBoard {
+            left: Tally::default(),
+            right: Tally::default(),
+            server: Side::Left, // the first ball always goes right
+            games_played: 0,
+            sudden_death: false,
+            winner: None,
+        }
+    }
+}
+
+impl ScoreBoard {
+    pub fn tally(&self, side: Side) -> &Tally {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    fn tally_mut(&mut self, side: Side) -> &mut Tally {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+
+    // puts `side` `head_start` up before the first serve, counted in whatever the scoring
+    // plays the first game to: points, or games of the first set in tennis
+    // never enough to win that outright, so it stops one short of the target
+    pub fn head_start(&mut self, side: Side, head_start: u32, scoring: &Scoring) {
+        match *scoring {
+            Scoring::Classic { points: 0 } => self.tally_mut(side).points = head_start.min(999),
+            Scoring::Classic { points } | Scoring::TableTennis { points, .. } => {
+                self.tally_mut(side).points = head_start.min(points.saturating_sub(1))
+            }
+            Scoring::Tennis { games, .. } => {
+                self.tally_mut(side).games = head_start.min(games.saturating_sub(1))
+            }
+        }
+    }
+
+    // `scorer` won a point, moves the games, sets, server and winner along
+    pub fn point(&mut self, scorer: Side, scoring: &Scoring) {
+        if self.winner.is_some() {
+            return;
+        }
+        let loser = scorer.other();
+        self.tally_mut(scorer).points += 1;
+        let won = self.tally(scorer).points;
+        let lost = self.tally(loser).points;
+
+        match *scoring {
+            Scoring::Classic { points } => {
+                self.tally_mut(scorer).points = won.min(999);
+                // whoever conceded serves, so the ball heads for the scorer like it always did
+                self.server = loser;
+                if points > 0 && won >= points {
+                    self.winner = Some(scorer);
+                }
+            }
+            Scoring::TableTennis {
+                points,
+                win_by,
+                serve_every,
+                best_of,
+            } => {
+                if won >= points && won >= lost + win_by {
+                    self.win_game(scorer);
+                    if self.tally(scorer).games > best_of / 2 {
+                        self.winner = Some(scorer);
+                    }
+                }
+                self.server = self.table_tennis_server(points, serve_every);
+            }
+            Scoring::Tennis { games, best_of } => {
+                // game point is 4 points (past 40) and two clear
+                if won >= 4 && won >= lost + 2 {
+                    self.win_game(scorer);
+                    let won_games = self.tally(scorer).games;
+                    let lost_games = self.tally(loser).games;
+                    if won_games >= games && won_games >= lost_games + 2 {
+                        self.left.games = 0;
+                        self.right.games = 0;
+                        self.tally_mut(scorer).sets += 1;
+                        if self.tally(scorer).sets > best_of / 2 {
+                            self.winner = Some(scorer);
+                        }
+                    }
+                }
+                self.server = self.first_server();
+            }
+        }
+
+        if self.sudden_death && self.winner.is_none() {
+            self.winner = Some(scorer);
+        }
+    }
+
+    // who is ahead on sets, then games, then points, None on a tie
+    pub fn leader(&self) -> Option<Side> {
+        let key = |tally: &Tally| (tally.sets, tally.games, tally.points);
+        match key(&self.left).cmp(&key(&self.right)) {
+            std::cmp::Ordering::Greater => Some(Side::Left),
+            std::cmp::Ordering::Less => Some(Side::Right),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    fn win_game(&mut self, scorer: Side) {
+        self.left.points = 0;
+        self.right.points = 0;
+        self.tally_mut(scorer).games += 1;
+        self.games_played += 1;
+    }
+
+    // serve alternates between games
+    fn first_server(&self) -> Side {
+        if self.games_played % 2 == 0 {
+            Side::Left
+        } else {
+            Side::Right
+        }
+    }
+
+    fn table_tennis_server(&self, points: u32, serve_every: u32) -> Side {
+        let played = self.left.points + self.right.points;
+        let deuce = points.saturating_sub(1);
+        let changes = if self.left.points >= deuce && self.right.points >= deuce {
+            // from deuce on the serve changes every point
+            deuce * 2 / serve_every.max(1) + (played - deuce * 2)
+        } else {
+            played / serve_every.max(1)
+        };
+        if changes % 2 == 0 {
+            self.first_server()
+        } else {
+            self.first_server().other()
+        }
+    }
+
+    // the big number above each player
+    pub fn points_text(&self, side: Side, scoring: &Scoring) -> String {
+        let mine = self.tally(side).points;
+        match scoring {
+            Scoring::Tennis { .. } => {
+                let theirs = self.tally(side.other()).points;
+                match (mine, theirs) {
+                    (m, t) if m >= 3 && t >= 3 && m > t => "AD".to_string(),
+                    (m, t) if m >= 3 && t >= 3 => "40".to_string(),
+                    (m, _) => ["0", "15", "30", "40"][m.min(3) as usize].to_string(),
+                }
+            }
+            _ => mine.to_string(),
+        }
+    }
+
+    // the line under the points: games, sets, who serves, and the result once there is one
+    pub fn status_text(&self, scoring: &Scoring) -> String {
+        if let Some(winner) = self.winner {
+            return format!("{:?} player wins!", winner);
+        }
+        if self.sudden_death {
+            return "sudden death, next point wins".to_string();
+        }
+        match scoring {
+            Scoring::Classic { points: 0 } => String::new(),
+            Scoring::Classic { points } => format!("first to {}", points),
+            Scoring::TableTennis { .. } => format!(
+                "games {} - {}   {:?} serves",
+                self.left.games, self.right.games, self.server
+            ),
+            Scoring::Tennis { .. } => {
+                let mut status = format!(
+                    "sets {} - {}   games {} - {}",
+                    self.left.sets, self.right.sets, self.left.games, self.right.games
+                );
+                let (left, right) = (self.left.points, self.right.points);
+                if left >= 3 && right >= 3 {
+                    status.push_str(&match left.cmp(&right) {
+                        std::cmp::Ordering::Equal => "   deuce".to_string(),
+                        std::cmp::Ordering::Greater => "   advantage Left".to_string(),
+                        std::cmp::Ordering::Less => "   advantage Right".to_string(),
+                    });
+                }
+                status
+            }
+        }
+    }
+}
+
+// a match against the clock, the leader when it runs out wins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeLimit {
+    pub seconds: f32,
+    pub tie_break: TieBreak,
+}
+
+// what happens when time runs out on a tie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TieBreak {
+    SuddenDeath,
+    Overtime(f32), // seconds of extra time, then sudden death if still tied
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockPhase {
+    Regulation,
+    Overtime,
+    Expired, // nothing left to count, sudden death or the match is over
+}
+
+// time left in a timed match, counted down in game time by the MatchClockSystem
+// only while a ball is in play, so it stops for serves and whenever the game is paused
+#[derive(Debug, Clone)]
+pub struct MatchClock {
+    pub remaining: f32,
+    pub phase: ClockPhase,
+    pub winner: Option<Side>, // the leader when time ran out, the mode decides what that means
+}
+
+impl Default for MatchClock {
+    fn default() -> Self {
+        MatchClock {
+            remaining: 0.0,
+            phase: ClockPhase::Expired,
+            winner: None,
+        }
+    }
+}
+
+impl MatchClock {
+    pub fn new(time_limit: Option<&TimeLimit>) -> Self {
+        match time_limit {
+            Some(limit) => MatchClock {
+                remaining: limit.seconds,
+                phase: ClockPhase::Regulation,
+                winner: None,
+            },
+            None => MatchClock::default(),
+        }
+    }
+
+    // counts `delta` seconds down and decides what happens when time runs out:
+    // the leader wins, a tie goes to overtime or straight to sudden death
+    pub fn tick(&mut self, delta: f32, scores: &mut ScoreBoard, tie_break: &TieBreak) {
+        if self.phase == ClockPhase::Expired {
+            return;
+        }
+        self.remaining = (self.remaining - delta).max(0.0);
+        if self.remaining > 0.0 {
+            return;
+        }
+        match (scores.leader(), self.phase, tie_break) {
+            (Some(leader), _, _) => {
+                self.phase = ClockPhase::Expired;
+                self.winner = Some(leader);
+            }
+            (None, ClockPhase::Regulation, TieBreak::Overtime(seconds)) => {
+                self.phase = ClockPhase::Overtime;
+                self.remaining = *seconds;
+            }
+            (None, _, _) => {
+                self.phase = ClockPhase::Expired;
+                scores.sudden_death = true;
+            }
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let seconds = self.remaining.ceil() as u32;
+        let clock = format!("{}:{:02}", seconds / 60, seconds % 60);
+        match self.phase {
+            ClockPhase::Regulation => clock,
+            ClockPhase::Overtime => format!("OT {}", clock),
+            ClockPhase::Expired => "0:00".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const L: Side = Side::Left;
+    const R: Side = Side::Right;
+
+    const TABLE_TENNIS: Scoring = Scoring::TableTennis {
+        points: 11,
+        win_by: 2,
+        serve_every: 2,
+        best_of: 5,
+    };
+    const TENNIS: Scoring = Scoring::Tennis {
+        games: 6,
+        best_of: 3,
+    };
+
+    fn play(board: &mut ScoreBoard, scoring: &Scoring, points: &[Side]) {
+        for &scorer in points {
+            board.point(scorer, scoring);
+        }
+    }
+
+    // `count` points in a row for `scorer`
+    fn run(board: &mut ScoreBoard, scoring: &Scoring, scorer: Side, count: u32) {
+        for _ in 0..count {
+            board.point(scorer, scoring);
+        }
+    }
+
+    // `games` tennis games each, alternating from the left
+    fn games_all(board: &mut ScoreBoard, games: u32) {
+        for _ in 0..games {
+            run(board, &TENNIS, L, 4);
+            run(board, &TENNIS, R, 4);
+        }
+    }
+
+    fn points(board: &ScoreBoard, scoring: &Scoring) -> (String, String) {
+        (board.points_text(L, scoring), board.points_text(R, scoring))
+    }
+
+    #[test]
+    fn classic_is_won_on_reaching_the_points() {
+        let scoring = Scoring::Classic { points: 3 };
+        let mut board = ScoreBoard::default();
+        play(&mut board, &scoring, &[L, R, L]);
+        assert_eq!(board.winner, None);
+        assert_eq!(board.server, R);
+        board.point(L, &scoring);
+        assert_eq!(board.winner, Some(L));
+        // nothing changes after the match is over
+        board.point(R, &scoring);
+        assert_eq!(board.right.points, 1);
+    }
+
+    #[test]
+    fn classic_without_a_target_never_ends() {
+        let scoring = Scoring::Classic { points: 0 };
+        let mut board = ScoreBoard::default();
+        run(&mut board, &scoring, L, 50);
+        assert_eq!(board.winner, None);
+        assert_eq!(board.left.points, 50);
+    }
+
+    #[test]
+    fn table_tennis_game_at_ten_all_goes_on_until_two_clear() {
+        let mut board = ScoreBoard::default();
+        run(&mut board, &TABLE_TENNIS, L, 10);
+        run(&mut board, &TABLE_TENNIS, R, 10);
+        board.point(L, &TABLE_TENNIS);
+        assert_eq!((board.left.points, board.right.points), (11, 10));
+        assert_eq!(board.left.games, 0);
+        board.point(L, &TABLE_TENNIS);
+        assert_eq!((board.left.points, board.right.points), (0, 0));
+        assert_eq!(board.left.games, 1);
+        assert_eq!(board.winner, None);
+    }
+
+    #[test]
+    fn table_tennis_best_of_five_ends_at_three_games() {
+        let mut board = ScoreBoard::default();
+        run(&mut board, &TABLE_TENNIS, L, 11);
+        run(&mut board, &TABLE_TENNIS, R, 11);
+        run(&mut board, &TABLE_TENNIS, L, 11);
+        assert_eq!(board.winner, None);
+        run(&mut board, &TABLE_TENNIS, L, 11);
+        assert_eq!((board.left.games, board.right.games), (3, 1));
+        assert_eq!(board.winner, Some(L));
+    }
+
+    #[test]
+    fn table_tennis_serve_changes_every_two_points_then_every_point_from_deuce() {
+        let mut board = ScoreBoard::default();
+        assert_eq!(board.server, L);
+        play(&mut board, &TABLE_TENNIS, &[L, R]);
+        assert_eq!(board.server, R);
+        play(&mut board, &TABLE_TENNIS, &[L]);
+        assert_eq!(board.server, R);
+        play(&mut board, &TABLE_TENNIS, &[R]);
+        assert_eq!(board.server, L);
+
+        // 20 points in is ten all
+        run(&mut board, &TABLE_TENNIS, L, 8);
+        run(&mut board, &TABLE_TENNIS, R, 8);
+        assert_eq!((board.left.points, board.right.points), (10, 10));
+        assert_eq!(board.server, L);
+        board.point(L, &TABLE_TENNIS);
+        assert_eq!(board.server, R);
+        board.point(R, &TABLE_TENNIS);
+        assert_eq!(board.server, L);
+    }
+
+    #[test]
+    fn table_tennis_first_serve_alternates_between_games() {
+        let mut board = ScoreBoard::default();
+        run(&mut board, &TABLE_TENNIS, L, 11);
+        assert_eq!(board.server, R);
+        run(&mut board, &TABLE_TENNIS, R, 11);
+        assert_eq!(board.server, L);
+    }
+
+    #[test]
+    fn tennis_points_count_fifteen_thirty_forty() {
+        let mut board = ScoreBoard::default();
+        let expected = ["0", "15", "30", "40"];
+        for (i, &text) in expected.iter().enumerate() {
+            assert_eq!(points(&board, &TENNIS), (text.to_string(), "0".to_string()));
+            if i < 3 {
+                board.point(L, &TENNIS);
+            }
+        }
+        board.point(L, &TENNIS);
+        assert_eq!(board.left.games, 1);
+        assert_eq!(points(&board, &TENNIS), ("0".to_string(), "0".to_string()));
+    }
+
+    #[test]
+    fn tennis_deuce_goes_to_advantage_and_back() {
+        let mut board = ScoreBoard::default();
+        play(&mut board, &TENNIS, &[L, L, L, R, R, R]);
+        assert_eq!(
+            points(&board, &TENNIS),
+            ("40".to_string(), "40".to_string())
+        );
+        assert!(board.status_text(&TENNIS).ends_with("deuce"));
+
+        board.point(L, &TENNIS);
+        assert_eq!(
+            points(&board, &TENNIS),
+            ("AD".to_string(), "40".to_string())
+        );
+        assert!(board.status_text(&TENNIS).ends_with("advantage Left"));
+
+        board.point(R, &TENNIS);
+        assert_eq!(
+            points(&board, &TENNIS),
+            ("40".to_string(), "40".to_string())
+        );
+        assert!(board.status_text(&TENNIS).ends_with("deuce"));
+
+        play(&mut board, &TENNIS, &[R, R]);
+        assert_eq!((board.left.games, board.right.games), (0, 1));
+    }
+
+    #[test]
+    fn tennis_set_at_six_five_goes_on_and_rolls_over_at_seven_five() {
+        let mut board = ScoreBoard::default();
+        games_all(&mut board, 5);
+        run(&mut board, &TENNIS, L, 4);
+        assert_eq!((board.left.games, board.right.games), (6, 5));
+        assert_eq!(board.left.sets, 0);
+
+        run(&mut board, &TENNIS, L, 4);
+        assert_eq!((board.left.games, board.right.games), (0, 0));
+        assert_eq!((board.left.sets, board.right.sets), (1, 0));
+        assert_eq!(board.winner, None);
+    }
+
+    #[test]
+    fn tennis_set_at_six_all_has_no_tie_break_and_goes_to_eight_six() {
+        let mut board = ScoreBoard::default();
+        games_all(&mut board, 6);
+        run(&mut board, &TENNIS, L, 4);
+        assert_eq!((board.left.games, board.right.games), (7, 6));
+        assert_eq!(board.left.sets, 0);
+
+        run(&mut board, &TENNIS, L, 4);
+        assert_eq!((board.left.games, board.right.games), (0, 0));
+        assert_eq!((board.left.sets, board.right.sets), (1, 0));
+    }
+
+    #[test]
+    fn tennis_serve_changes_every_game() {
+        let mut board = ScoreBoard::default();
+        assert_eq!(board.server, L);
+        run(&mut board, &TENNIS, R, 4);
+        assert_eq!(board.server, R);
+        run(&mut board, &TENNIS, R, 2);
+        assert_eq!(board.server, R);
+        run(&mut board, &TENNIS, L, 4);
+        assert_eq!(board.server, L);
+    }
+
+    #[test]
+    fn tennis_best_of_three_ends_at_two_sets() {
+        let mut board = ScoreBoard::default();
+        run(&mut board, &TENNIS, L, 24);
+        assert_eq!(board.left.sets, 1);
+        assert_eq!(board.winner, None);
+        run(&mut board, &TENNIS, L, 24);
+        assert_eq!(board.winner, Some(L));
+    }
+
+    #[test]
+    fn sudden_death_gives_the_match_to_the_next_scorer() {
+        let mut board = ScoreBoard {
+            sudden_death: true,
+            ..ScoreBoard::default()
+        };
+        board.point(R, &TABLE_TENNIS);
+        assert_eq!(board.winner, Some(R));
+    }
+
+    #[test]
+    fn the_leader_is_decided_by_sets_then_games_then_points() {
+        let mut board = ScoreBoard::default();
+        assert_eq!(board.leader(), None);
+        board.left.points = 3;
+        assert_eq!(board.leader(), Some(L));
+        board.right.games = 1;
+        assert_eq!(board.leader(), Some(R));
+        board.left.sets = 1;
+        assert_eq!(board.leader(), Some(L));
+    }
+
+    fn timed(seconds: f32) -> MatchClock {
+        MatchClock::new(Some(&TimeLimit {
+            seconds,
+            tie_break: TieBreak::SuddenDeath,
+        }))
+    }
+
+    #[test]
+    fn the_clock_gives_the_match_to_the_leader_when_time_runs_out() {
+        let mut clock = timed(10.0);
+        let mut board = ScoreBoard::default();
+        board.right.points = 2;
+        clock.tick(6.0, &mut board, &TieBreak::SuddenDeath);
+        assert_eq!(clock.phase, ClockPhase::Regulation);
+        assert_eq!(clock.winner, None);
+        clock.tick(6.0, &mut board, &TieBreak::SuddenDeath);
+        assert_eq!(clock.phase, ClockPhase::Expired);
+        assert_eq!(clock.winner, Some(R));
+        // the clock decides, the scoreboard is left to the mode
+        assert_eq!(board.winner, None);
+        assert!(!board.sudden_death);
+    }
+
+    #[test]
+    fn a_tie_goes_to_overtime_then_sudden_death() {
+        let tie_break = TieBreak::Overtime(30.0);
+        let mut clock = timed(10.0);
+        let mut board = ScoreBoard::default();
+        board.left.points = 1;
+        board.right.points = 1;
+
+        clock.tick(10.0, &mut board, &tie_break);
+        assert_eq!(clock.phase, ClockPhase::Overtime);
+        assert_eq!(clock.remaining, 30.0);
+        assert_eq!(clock.text(), "OT 0:30");
+        assert!(!board.sudden_death);
+
+        clock.tick(31.0, &mut board, &tie_break);
+        assert_eq!(clock.phase, ClockPhase::Expired);
+        assert_eq!(clock.winner, None);
+        assert!(board.sudden_death);
+
+        // nothing left to count down
+        clock.tick(5.0, &mut board, &tie_break);
+        assert_eq!(clock.remaining, 0.0);
+        board.point(L, &Scoring::Classic { points: 0 });
+        assert_eq!(board.winner, Some(L));
+    }
+
+    #[test]
+    fn a_lead_taken_in_overtime_wins_when_it_runs_out() {
+        let tie_break = TieBreak::Overtime(30.0);
+        let mut clock = timed(10.0);
+        let mut board = ScoreBoard::default();
+        clock.tick(10.0, &mut board, &tie_break);
+        assert_eq!(clock.phase, ClockPhase::Overtime);
+        board.left.points = 1;
+        clock.tick(30.0, &mut board, &tie_break);
+        assert_eq!(clock.winner, Some(L));
+        assert!(!board.sudden_death);
+    }
+
+    #[test]
+    fn a_tie_without_overtime_goes_straight_to_sudden_death() {
+        let mut clock = timed(10.0);
+        let mut board = ScoreBoard::default();
+        clock.tick(10.0, &mut board, &TieBreak::SuddenDeath);
+        assert_eq!(clock.phase, ClockPhase::Expired);
+        assert_eq!(clock.winner, None);
+        assert!(board.sudden_death);
+    }
+
+    #[test]
+    fn an_untimed_clock_never_runs() {
+        let mut clock = MatchClock::new(None);
+        let mut board = ScoreBoard::default();
+        clock.tick(1000.0, &mut board, &TieBreak::SuddenDeath);
+        assert_eq!(clock.winner, None);
+        assert!(!board.sudden_death);
+    }
+
+    #[test]
+    fn a_head_start_is_counted_in_points_or_games_and_never_wins_outright() {
+        let mut board = ScoreBoard::default();
+        board.head_start(L, 5, &Scoring::Classic { points: 0 });
+        assert_eq!(board.left.points, 5);
+
+        let mut board = ScoreBoard::default();
+        board.head_start(L, 9, &Scoring::Classic { points: 5 });
+        assert_eq!(board.left.points, 4);
+        assert_eq!(board.winner, None);
+
+        let mut board = ScoreBoard::default();
+        board.head_start(R, 9, &TABLE_TENNIS);
+        assert_eq!(board.right.points, 9);
+        board.head_start(R, 12, &TABLE_TENNIS);
+        assert_eq!(board.right.points, 10);
+        assert_eq!(board.right.games, 0);
+
+        let mut board = ScoreBoard::default();
+        board.head_start(L, 9, &TENNIS);
+        assert_eq!((board.left.points, board.left.games), (0, 5));
+        run(&mut board, &TENNIS, L, 4);
+        assert_eq!(board.left.sets, 1);
+    }
+}
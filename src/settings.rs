@@ -0,0 +1,67 @@
+use amethyst::{config::Config, utils::application_root_dir};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+use crate::audio::AudioMixer;
+use crate::handicap::Handicaps;
+use crate::profiles::{default_profiles, Profile};
+
+// written next to the shipped config, but not checked in
+const USER_SETTINGS_FILE: &str = "user_settings.ron";
+
+// everything the player can change from inside the game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    pub audio: AudioMixer,
+    pub high_scores: HighScores,
+    pub handicaps: Handicaps, // for guests, profiles keep their own
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        UserSettings {
+            audio: AudioMixer::default(),
+            high_scores: HighScores::default(),
+            handicaps: Handicaps::default(),
+            profiles: default_profiles(),
+        }
+    }
+}
+
+// best results in the single player modes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    pub squash: u32, // most returns in a row
+}
+
+fn user_settings_path() -> amethyst::Result<PathBuf> {
+    Ok(application_root_dir()?
+        .join("config")
+        .join(USER_SETTINGS_FILE))
+}
+
+// missing or broken settings file just means defaults
+pub fn load_user_settings() -> UserSettings {
+    match user_settings_path() {
+        Ok(path) if path.exists() => UserSettings::load(path),
+        Ok(_) => UserSettings::default(),
+        Err(err) => {
+            warn!("Could not locate user settings: {}", err);
+            UserSettings::default()
+        }
+    }
+}
+
+pub fn save_user_settings(settings: &UserSettings) {
+    let result = user_settings_path()
+        .map_err(|err| err.to_string())
+        .and_then(|path| settings.write(path).map_err(|err| err.to_string()));
+    if let Err(err) = result {
+        warn!("Could not save user settings: {}", err);
+    }
+}
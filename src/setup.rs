@@ -0,0 +1,318 @@
+use amethyst::{
+    ecs::prelude::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, UiText, UiTransform},
+};
+
+use log::warn;
+
+use crate::ai::AiConfig;
+use crate::handicap::{Handicap, Handicaps, Setting, SETTINGS};
+use crate::loading::GameAssets;
+use crate::modes::Classic;
+use crate::pong::{Pong, Side};
+use crate::profiles::{Keys, Player, Players, Profile};
+use crate::settings::{load_user_settings, save_user_settings};
+
+const SELECTED_COLOR: [f32; 4] = [1., 1., 0., 1.];
+const NORMAL_COLOR: [f32; 4] = [1., 1., 1., 1.];
+
+// one line of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Player(Side), // which profile plays that side, or a guest
+    Handicap(Side, Setting),
+}
+
+// shown before a classic match: each player picks a profile and a handicap,
+// then the match starts in its place
+// a profile brings its own handicap, guests share the one saved in the user settings
+#[derive(Default)]
+pub struct MatchSetup {
+    computer: bool, // the right player is the computer
+    profiles: Vec<Profile>,
+    chosen: [Option<usize>; 2], // index into `profiles` for left and right, None for a guest
+    guests: Handicaps,
+    layout: Vec<Row>,
+    selected: usize,
+    rows: Vec<Entity>, // one line of text per entry in `layout`
+    labels: Vec<Entity>,
+}
+
+impl SimpleState for MatchSetup {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        // everyone starts out as a guest
+        let settings = load_user_settings();
+        self.profiles = settings.profiles;
+        self.guests = settings.handicaps;
+        *world.write_resource::<Handicaps>() = self.guests.clone();
+        self.chosen = [None, None];
+        self.layout = Vec::new();
+        for &side in &[Side::Left, Side::Right] {
+            if !(self.computer && side == Side::Right) {
+                self.layout.push(Row::Player(side));
+            }
+            self.layout
+                .extend(SETTINGS.iter().map(|&setting| Row::Handicap(side, setting)));
+        }
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        let title = world
+            .create_entity()
+            .with(UiTransform::new(
+                "setup_title".to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                -20.,
+                2.,
+                400.,
+                50.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "PLAYERS".to_string(),
+                NORMAL_COLOR,
+                40.,
+            ))
+            .build();
+        let hint = world
+            .create_entity()
+            .with(UiTransform::new(
+                "setup_hint".to_string(),
+                Anchor::BottomMiddle,
+                Anchor::BottomMiddle,
+                0.,
+                20.,
+                2.,
+                480.,
+                30.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "Up/Down select  Left/Right adjust  Enter play  Esc back".to_string(),
+                NORMAL_COLOR,
+                16.,
+            ))
+            .build();
+        self.labels = vec![title, hint];
+
+        self.rows = self
+            .layout
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        format!("setup_{:?}", row),
+                        Anchor::Middle,
+                        Anchor::Middle,
+                        0.,
+                        160. - 34. * i as f32,
+                        2.,
+                        480.,
+                        32.,
+                    ))
+                    .with(UiText::new(font.clone(), String::new(), NORMAL_COLOR, 20.))
+                    .build()
+            })
+            .collect();
+
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        // keep anything else in the settings file as it was, only the handicaps are ours
+        let mut settings = load_user_settings();
+        settings.handicaps = self.guests.clone();
+        for profile in &self.profiles {
+            if let Some(saved) = settings
+                .profiles
+                .iter_mut()
+                .find(|saved| saved.name == profile.name)
+            {
+                saved.handicap = profile.handicap.clone();
+            }
+        }
+        save_user_settings(&settings);
+
+        let entities = self
+            .rows
+            .drain(..)
+            .chain(self.labels.drain(..))
+            .collect::<Vec<_>>();
+        if let Err(err) = world.delete_entities(&entities) {
+            warn!("Could not clean up match setup screen: {}", err);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) {
+                return Trans::Quit;
+            }
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if is_key_down(event, VirtualKeyCode::Return) {
+                // the match takes our place, leaving it goes straight back to the menu
+                let mut mode = Classic::default();
+                if self.computer {
+                    let difficulty = data.world.read_resource::<AiConfig>().difficulty;
+                    mode = mode.with_computer(Side::Right, difficulty);
+                }
+                data.world.insert(self.players());
+                return Trans::Switch(Box::new(Pong::new(Box::new(mode))));
+            }
+
+            let row = self.layout[self.selected];
+            let steps = if is_key_down(event, VirtualKeyCode::Up) {
+                self.selected = (self.selected + self.layout.len() - 1) % self.layout.len();
+                0
+            } else if is_key_down(event, VirtualKeyCode::Down) {
+                self.selected = (self.selected + 1) % self.layout.len();
+                0
+            } else if is_key_down(event, VirtualKeyCode::Left) {
+                -1
+            } else if is_key_down(event, VirtualKeyCode::Right) {
+                1
+            } else {
+                return Trans::None;
+            };
+            match row {
+                _ if steps == 0 => {}
+                Row::Player(side) => self.choose(data.world, side, steps),
+                Row::Handicap(side, setting) => {
+                    let mut handicap = self.handicap_mut(side).clone();
+                    setting.adjust(&mut handicap, steps);
+                    *data.world.write_resource::<Handicaps>().side_mut(side) = handicap.clone();
+                    *self.handicap_mut(side) = handicap;
+                }
+            }
+            self.refresh(data.world);
+        }
+        Trans::None
+    }
+}
+
+impl MatchSetup {
+    pub fn against_computer() -> Self {
+        MatchSetup {
+            computer: true,
+            ..MatchSetup::default()
+        }
+    }
+
+    fn chosen(&self, side: Side) -> Option<usize> {
+        match side {
+            Side::Left => self.chosen[0],
+            Side::Right => self.chosen[1],
+        }
+    }
+
+    // steps through the guest and every profile the other side hasn't taken
+    fn choose(&mut self, world: &mut World, side: Side, steps: i32) {
+        let taken = self.chosen(side.other());
+        // 0 is the guest, profile i is i + 1
+        let options = self.profiles.len() as i32 + 1;
+        let mut choice = self.chosen(side).map_or(0, |i| i as i32 + 1);
+        let profile = loop {
+            choice = (choice + steps).rem_euclid(options);
+            let profile = if choice == 0 {
+                None
+            } else {
+                Some(choice as usize - 1)
+            };
+            if profile.is_none() || profile != taken {
+                break profile;
+            }
+        };
+        match side {
+            Side::Left => self.chosen[0] = profile,
+            Side::Right => self.chosen[1] = profile,
+        }
+        *world.write_resource::<Handicaps>().side_mut(side) = self.handicap_mut(side).clone();
+    }
+
+    // where the handicap of whoever plays `side` is kept
+    fn handicap_mut(&mut self, side: Side) -> &mut Handicap {
+        match self.chosen(side) {
+            Some(i) => &mut self.profiles[i].handicap,
+            None => self.guests.side_mut(side),
+        }
+    }
+
+    fn player(&self, side: Side) -> Player {
+        match (self.chosen(side), side) {
+            (Some(i), _) => Player::from(&self.profiles[i]),
+            (None, Side::Left) => Player::guest("Player 1", Keys::LeftHand),
+            (None, Side::Right) => Player::guest("Player 2", Keys::RightHand),
+        }
+    }
+
+    fn players(&self) -> Players {
+        let left = self.player(Side::Left);
+        let right = if self.computer {
+            // the computer doesn't mind which keys it's left with
+            Player::guest("Computer", left.keys.other())
+        } else {
+            self.player(Side::Right)
+        };
+        Players { left, right }
+    }
+
+    // redraws every row from the current choices
+    fn refresh(&self, world: &mut World) {
+        let handicaps = world.read_resource::<Handicaps>().clone();
+        let players = self.players();
+        let mut texts = world.write_storage::<UiText>();
+        for (i, (row, entity)) in self.layout.iter().zip(&self.rows).enumerate() {
+            if let Some(text) = texts.get_mut(*entity) {
+                text.text = match *row {
+                    // e.g. "Left   Player 1 (W/S)"
+                    Row::Player(side) => {
+                        let player = players.side(side);
+                        let name = match player.profile {
+                            Some(_) => player.name.as_str(),
+                            None => "Guest",
+                        };
+                        format!(
+                            "{:<6} {} ({})",
+                            format!("{:?}", side),
+                            name,
+                            player.keys.label()
+                        )
+                    }
+                    // e.g. "Left   Paddle height   125%"
+                    Row::Handicap(side, setting) => {
+                        let player = match side {
+                            Side::Right if self.computer => "CPU".to_string(),
+                            _ => format!("{:?}", side),
+                        };
+                        format!(
+                            "{:<6} {:<14} {:>5}",
+                            player,
+                            setting.label(),
+                            setting.value_text(handicaps.side(side))
+                        )
+                    }
+                };
+                text.color = if i == self.selected {
+                    SELECTED_COLOR
+                } else {
+                    NORMAL_COLOR
+                };
+            }
+        }
+    }
+}
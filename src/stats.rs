@@ -0,0 +1,89 @@
+use amethyst::{config::Config, utils::application_root_dir};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::Difficulty;
+use crate::pong::Side;
+
+// written after every match, not checked in
+const MATCH_STATS_FILE: &str = "match_stats.ron";
+
+// what happened in the current (or last) match, kept up to date by the MatchStatsSystem
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchStats {
+    pub mode: String,
+    pub players: [String; 2],               // left, right
+    pub duration: f32,                      // seconds of play, pauses don't count
+    pub goals: [u32; 2], // left, right, counted from goals so any scoring rules work
+    pub rallies: Vec<u32>, // paddle returns before each goal
+    pub rally: u32,      // returns so far in the rally being played
+    pub computers: Vec<(Side, Difficulty)>, // which paddles the computer played
+    pub difficulty: Vec<DifficultySample>,
+    pub winner: Option<Side>,
+}
+
+// the computer's level at some point of the match
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DifficultySample {
+    pub time: f32, // seconds into the match
+    pub side: Side,
+    pub level: f32,
+}
+
+impl MatchStats {
+    pub fn new(mode: &str) -> Self {
+        MatchStats {
+            mode: mode.to_string(),
+            ..MatchStats::default()
+        }
+    }
+
+    pub fn goals(&self, side: Side) -> u32 {
+        match side {
+            Side::Left => self.goals[0],
+            Side::Right => self.goals[1],
+        }
+    }
+
+    // a match that never got going isn't worth keeping
+    pub fn is_empty(&self) -> bool {
+        self.goals == [0, 0] && self.rally == 0
+    }
+
+    pub fn summary(&self) -> String {
+        let longest = self.rallies.iter().max().copied().unwrap_or(0);
+        let mut summary = format!(
+            "{} match, {:.0}s, goals {}-{}, {} rallies, longest {}",
+            self.mode,
+            self.duration,
+            self.goals[0],
+            self.goals[1],
+            self.rallies.len(),
+            longest
+        );
+        for &(side, setting) in &self.computers {
+            let levels = self
+                .difficulty
+                .iter()
+                .filter(|sample| sample.side == side)
+                .map(|sample| format!("{:.2}", sample.level))
+                .collect::<Vec<_>>();
+            summary += &format!(", {:?} computer {:?} [{}]", side, setting, levels.join(" "));
+        }
+        summary
+    }
+}
+
+pub fn save_match_stats(stats: &MatchStats) {
+    let result = application_root_dir()
+        .map_err(|err| err.to_string())
+        .and_then(|root| {
+            stats
+                .write(root.join("config").join(MATCH_STATS_FILE))
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        warn!("Could not save match stats: {}", err);
+    }
+}
@@ -0,0 +1,42 @@
+use amethyst::{
+    core::SystemDesc,
+    derive::SystemDesc,
+    ecs::prelude::{Join, ReadStorage, System, SystemData, World, WriteStorage},
+    ui::UiText,
+};
+
+use crate::pong::{Ability, AbilityIndicator, Paddle};
+
+// shows each player's dash and smash cooldowns under their score
+#[derive(SystemDesc)]
+pub struct AbilityHudSystem;
+
+impl<'s> System<'s> for AbilityHudSystem {
+    type SystemData = (
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, AbilityIndicator>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (paddles, indicators, mut texts): Self::SystemData) {
+        for (indicator, text) in (&indicators, &mut texts).join() {
+            if let Some(paddle) = paddles.join().find(|paddle| paddle.side == indicator.side) {
+                text.text = format!(
+                    "dash {}  smash {}",
+                    status(&paddle.dash),
+                    status(&paddle.smash)
+                );
+            }
+        }
+    }
+}
+
+fn status(ability: &Ability) -> String {
+    if ability.is_active() {
+        "now".to_string()
+    } else if ability.is_ready() {
+        "ready".to_string()
+    } else {
+        format!("{:.1}", ability.cooldown)
+    }
+}
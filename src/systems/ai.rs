@@ -0,0 +1,150 @@
+use amethyst::{
+    core::{timing::Time, SystemDesc, Transform},
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadStorage, System, SystemData, World, Write, WriteStorage},
+};
+
+use rand::Rng;
+
+use crate::ai::{AiConfig, AiPaddle, AiState, Difficulty};
+use crate::physics::{Collider, Velocity};
+use crate::pong::{Ball, Orientation, Paddle, Side, ARENA_HEIGHT};
+use crate::stats::{DifficultySample, MatchStats};
+
+// steers every paddle with an AiPaddle: waits its reaction time once a ball heads its way,
+// then moves towards where it thinks the ball will arrive, off by up to its error
+// adaptive paddles also re-aim their level after every point
+#[derive(SystemDesc)]
+pub struct AiSystem;
+
+impl<'s> System<'s> for AiSystem {
+    type SystemData = (
+        WriteStorage<'s, AiPaddle>,
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, Velocity>,
+        Read<'s, Time>,
+        Read<'s, AiConfig>,
+        Write<'s, AiState>,
+        Write<'s, MatchStats>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut ais,
+            paddles,
+            balls,
+            colliders,
+            transforms,
+            mut velocities,
+            time,
+            config,
+            mut state,
+            mut stats,
+        ): Self::SystemData,
+    ) {
+        if (&ais).join().next().is_none() {
+            return; // nobody is playing the computer
+        }
+
+        if stats.rallies.len() > state.points_seen {
+            state.points_seen = stats.rallies.len();
+            let curve = &config.adaptive;
+            for (ai, paddle) in (&mut ais, &paddles).join() {
+                if ai.difficulty != Difficulty::Adaptive {
+                    continue;
+                }
+                // the lead of whoever it's playing against
+                let side = paddle.side;
+                let lead = stats.goals(side.other()) as i32 - stats.goals(side) as i32;
+                let change = (curve.target(lead, &stats.rallies) - ai.level)
+                    .max(-curve.step)
+                    .min(curve.step);
+                ai.level += change;
+                let sample = DifficultySample {
+                    time: stats.duration,
+                    side,
+                    level: ai.level,
+                };
+                stats.difficulty.push(sample);
+            }
+        }
+
+        let in_play = (&balls, &transforms, &velocities)
+            .join()
+            .map(|(_, transform, velocity)| {
+                let translation = transform.translation();
+                ([translation.x, translation.y], velocity.0)
+            })
+            .collect::<Vec<_>>();
+        let delta = time.delta_seconds();
+        let mut rng = rand::thread_rng();
+
+        for (ai, paddle, collider, transform, velocity) in
+            (&mut ais, &paddles, &colliders, &transforms, &mut velocities).join()
+        {
+            velocity.0 = [0.0, 0.0];
+            if delta <= 0.0 || paddle.orientation != Orientation::Vertical {
+                continue; // paused, or a paddle it doesn't know how to play
+            }
+            let skill = config.easiest.mix(&config.hardest, ai.level);
+            let x = transform.translation().x;
+            let y = transform.translation().y;
+
+            // the ball that gets here first, if any is coming
+            let incoming = in_play
+                .iter()
+                .filter(|(_, velocity)| match paddle.side {
+                    Side::Left => velocity[0] < 0.0,
+                    Side::Right => velocity[0] > 0.0,
+                })
+                .map(|(position, velocity)| ((x - position[0]) / velocity[0], *position, *velocity))
+                .filter(|(arrival, _, _)| *arrival >= 0.0)
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+            let target = match incoming {
+                Some((arrival, position, velocity)) => {
+                    if !ai.tracking {
+                        ai.tracking = true;
+                        ai.reaction = skill.reaction;
+                        ai.aim_error = if skill.error > 0.0 {
+                            rng.gen_range(-skill.error, skill.error)
+                        } else {
+                            0.0
+                        };
+                    }
+                    ai.reaction -= delta;
+                    if ai.reaction > 0.0 {
+                        continue;
+                    }
+                    landing_height(position[1] + velocity[1] * arrival) + ai.aim_error
+                }
+                None => {
+                    // nothing to do, wait in the middle
+                    ai.tracking = false;
+                    ARENA_HEIGHT / 2.0
+                }
+            };
+
+            let half_length = collider.half_height();
+            let step = paddle.speed * skill.speed * delta;
+            let next = (y + (target - y).max(-step).min(step))
+                .min(ARENA_HEIGHT - half_length)
+                .max(half_length); // same limits as a player's paddle
+            velocity.0[1] = (next - y) / delta;
+        }
+    }
+}
+
+// where a ball heading for `height` ends up after bouncing between the top and bottom walls
+fn landing_height(height: f32) -> f32 {
+    let folded = height.rem_euclid(2.0 * ARENA_HEIGHT);
+    if folded > ARENA_HEIGHT {
+        2.0 * ARENA_HEIGHT - folded
+    } else {
+        folded
+    }
+}
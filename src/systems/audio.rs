@@ -0,0 +1,173 @@
+use amethyst::{
+    assets::AssetStorage,
+    audio::Source,
+    core::SystemDesc,
+    ecs::prelude::{Read, ReadExpect, System, SystemData, World},
+    shrev::{EventChannel, ReaderId},
+};
+
+use log::warn;
+use rand::Rng;
+use rodio::{source::Buffered, Decoder, Device, Source as _, SpatialSink};
+
+use std::{collections::HashMap, io::Cursor};
+
+use crate::audio::{
+    AudioDevice, AudioMixer, SoundEffect, SoundSpec, Sounds, BRICK_BREAK_SOUND, MAX_VOICES,
+    PADDLE_BOUNCE_SOUND, SCORE_SOUND, WALL_BOUNCE_SOUND,
+};
+use crate::events::GameEvent;
+use crate::pong::{ARENA_WIDTH, BALL_VELOCITY_X, BALL_VELOCITY_Y};
+
+// listener's ears for stereo panning, a sound emitted at x = -1 is fully left, x = 1 fully right
+const LEFT_EAR: [f32; 3] = [-1.0, 0.0, 0.0];
+const RIGHT_EAR: [f32; 3] = [1.0, 0.0, 0.0];
+
+// an effect decoded once, clones share the samples instead of decoding the file again
+type Samples = Buffered<Decoder<Cursor<Vec<u8>>>>;
+
+// the event reader has to be registered on the channel, so this one needs a hand written descriptor
+#[derive(Default)]
+pub struct AudioSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, AudioSystem> for AudioSystemDesc {
+    fn build(self, world: &mut World) -> AudioSystem {
+        <AudioSystem as System<'_>>::SystemData::setup(world);
+        let reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        AudioSystem {
+            reader,
+            samples: HashMap::new(),
+            voices: Vec::new(),
+        }
+    }
+}
+
+// plays sound effects for gameplay events, music is handled by the MusicSystem
+pub struct AudioSystem {
+    reader: ReaderId<GameEvent>,
+    samples: HashMap<&'static str, Samples>,
+    voices: Vec<SpatialSink>, // currently playing effects, oldest first
+}
+
+impl<'s> System<'s> for AudioSystem {
+    type SystemData = (
+        Read<'s, EventChannel<GameEvent>>,
+        Read<'s, AssetStorage<Source>>,
+        ReadExpect<'s, Sounds>,
+        Read<'s, AudioMixer>,
+        Option<Read<'s, AudioDevice>>,
+    );
+
+    fn run(&mut self, (events, storage, sounds, mixer, device): Self::SystemData) {
+        // finished voices no longer count towards the cap
+        self.voices.retain(|voice| !voice.empty());
+
+        // always drain the channel, even without an audio device, so events don't pile up
+        for event in events.read(&mut self.reader) {
+            let device = match device {
+                Some(ref device) => &device.0,
+                None => continue,
+            };
+            let (name, pitch_scale, x) = match event {
+                GameEvent::WallBounce { position, speed } => {
+                    (WALL_BOUNCE_SOUND, speed_pitch(*speed), position[0])
+                }
+                GameEvent::PaddleBounce {
+                    position, speed, ..
+                } => (PADDLE_BOUNCE_SOUND, speed_pitch(*speed), position[0]),
+                GameEvent::BrickBreak { position, speed } => {
+                    (BRICK_BREAK_SOUND, speed_pitch(*speed), position[0])
+                }
+                GameEvent::Score { position, .. } => (SCORE_SOUND, 1.0, position[0]),
+                GameEvent::BallRemoved => continue,
+            };
+            // sounds missing from the manifest are simply not played
+            let effect = match sounds.get(name) {
+                Some(effect) => effect,
+                None => continue,
+            };
+            if let Some(samples) = self.samples(name, effect, &storage) {
+                self.play(
+                    device,
+                    samples,
+                    &effect.spec,
+                    mixer.sfx_volume(),
+                    pitch_scale,
+                    pan(x),
+                );
+            }
+        }
+    }
+}
+
+impl AudioSystem {
+    // decodes an effect the first time it's played, None while it's still loading
+    fn samples(
+        &mut self,
+        name: &'static str,
+        effect: &SoundEffect,
+        storage: &AssetStorage<Source>,
+    ) -> Option<Samples> {
+        if let Some(samples) = self.samples.get(name) {
+            return Some(samples.clone());
+        }
+        let source = storage.get(&effect.handle)?;
+        match Decoder::new(Cursor::new(source.bytes.clone())) {
+            Ok(decoder) => {
+                let samples = decoder.buffered();
+                self.samples.insert(name, samples.clone());
+                Some(samples)
+            }
+            Err(err) => {
+                warn!("Could not decode sound effect {}: {}", name, err);
+                None
+            }
+        }
+    }
+
+    fn play(
+        &mut self,
+        device: &Device,
+        samples: Samples,
+        spec: &SoundSpec,
+        bus_volume: f32,
+        pitch_scale: f32,
+        pan: f32,
+    ) {
+        if self.voices.len() >= MAX_VOICES {
+            // dropping a sink stops it
+            self.voices.remove(0);
+        }
+
+        let mut rng = rand::thread_rng();
+        let volume = spec.volume + vary(&mut rng, spec.volume_variation);
+        let pitch = (spec.pitch + vary(&mut rng, spec.pitch_variation)) * pitch_scale;
+
+        let voice = SpatialSink::new(device, [pan, 0.0, 0.0], LEFT_EAR, RIGHT_EAR);
+        voice.set_volume((volume * bus_volume).max(0.0));
+        voice.append(samples.speed(pitch.max(0.1)));
+        self.voices.push(voice);
+    }
+}
+
+fn vary<R: Rng>(rng: &mut R, amount: f32) -> f32 {
+    if amount > 0.0 {
+        rng.gen_range(-amount, amount)
+    } else {
+        0.0
+    }
+}
+
+// faster ball, higher pitch, relative to the serve speed
+fn speed_pitch(speed: f32) -> f32 {
+    let serve_speed =
+        (BALL_VELOCITY_X * BALL_VELOCITY_X + BALL_VELOCITY_Y * BALL_VELOCITY_Y).sqrt();
+    (speed / serve_speed).sqrt().max(0.5).min(2.0)
+}
+
+// maps the arena x coordinate onto the -1 (left) .. 1 (right) range between the ears
+fn pan(x: f32) -> f32 {
+    ((x / ARENA_WIDTH) * 2.0 - 1.0).max(-1.0).min(1.0)
+}
@@ -1,72 +1,161 @@
 use amethyst::{
-    assets::AssetStorage,
-    audio::{output::Output, Source},
     core::{SystemDesc, Transform},
-    derive::SystemDesc,
-    ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, SystemData, World, WriteStorage},
+    ecs::prelude::{Read, System, SystemData, World, Write, WriteStorage},
+    shrev::{EventChannel, ReaderId},
 };
 
-use std::ops::Deref;
+use crate::events::GameEvent;
+use crate::physics::{Contact, Velocity};
+use crate::pong::{Ball, Paddle};
+use crate::rules::Rules;
 
-use crate::audio::{play_bounce_sound, Sounds};
-use crate::pong::{Ball, Paddle, Side, ARENA_HEIGHT};
+#[derive(Default)]
+pub struct BounceSystemDesc;
 
-pub struct BounceSystem;
+impl<'a, 'b> SystemDesc<'a, 'b, BounceSystem> for BounceSystemDesc {
+    fn build(self, world: &mut World) -> BounceSystem {
+        <BounceSystem as System<'_>>::SystemData::setup(world);
+        let reader = world.fetch_mut::<EventChannel<Contact>>().register_reader();
+        BounceSystem { reader }
+    }
+}
+
+// turns contacts involving a ball into bounces
+// the contact normal already says whether the ball hit a face, an edge or a corner,
+// so the ball is pushed out along it and bounced off it, relative to whatever it hit,
+// which lets a paddle moving up or down swat the ball with its top or bottom
+pub struct BounceSystem {
+    reader: ReaderId<Contact>,
+}
 
 impl<'s> System<'s> for BounceSystem {
     type SystemData = (
         WriteStorage<'s, Ball>,
-        ReadStorage<'s, Paddle>,
-        ReadStorage<'s, Transform>,
-        Read<'s, AssetStorage<Source>>,
-        ReadExpect<'s, Sounds>,
-        Option<Read<'s, Output>>,
+        WriteStorage<'s, Paddle>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Transform>,
+        Read<'s, EventChannel<Contact>>,
+        Write<'s, EventChannel<GameEvent>>,
+        Read<'s, Rules>,
     );
 
     fn run(
         &mut self,
-        (mut balls, paddles, transforms, storage, sounds, audio_output): Self::SystemData,
+        (
+            mut balls,
+            mut paddles,
+            mut velocities,
+            mut transforms,
+            contacts,
+            mut events,
+            rules,
+        ): Self::SystemData,
     ) {
-        for (ball, transform) in (&mut balls, &transforms).join() {
-            let ball_x = transform.translation().x;
-            let ball_y = transform.translation().y;
+        for contact in contacts.read(&mut self.reader) {
+            // look at every contact from the ball's side, `b` is whatever it hit
+            let contact = if balls.contains(contact.a) {
+                *contact
+            } else if balls.contains(contact.b) {
+                contact.flipped()
+            } else {
+                continue;
+            };
+            // walls and other static things don't move
+            let other_velocity = velocities.get(contact.b).map_or([0.0, 0.0], |v| v.0);
+            let (velocity, transform) =
+                match (velocities.get_mut(contact.a), transforms.get_mut(contact.a)) {
+                    (Some(velocity), Some(transform)) => (velocity, transform),
+                    _ => continue,
+                };
+            let normal = contact.normal;
+
+            // never leave the ball overlapping, or it gets bounced again next frame and sticks
+            transform.prepend_translation_x(normal[0] * contact.depth);
+            transform.prepend_translation_y(normal[1] * contact.depth);
+            let position = [transform.translation().x, transform.translation().y];
 
-            // check vertical boundary collisions
-            if (ball_y <= ball.radius && ball.velocity[1] < 0.0)
-                || (ball_y >= ARENA_HEIGHT - ball.radius && ball.velocity[1] > 0.0)
-            {
-                ball.velocity[1] = -ball.velocity[1];
-                play_bounce_sound(&*sounds, &storage, audio_output.as_ref().map(|o| o.deref()));
+            // mirror the velocity relative to the other body around the normal,
+            // unless they're already separating (contacts can last several frames)
+            let relative = [
+                velocity.0[0] - other_velocity[0],
+                velocity.0[1] - other_velocity[1],
+            ];
+            let into = relative[0] * normal[0] + relative[1] * normal[1];
+            if into >= 0.0 {
+                continue;
             }
+            velocity.0[0] -= 2.0 * into * normal[0];
+            velocity.0[1] -= 2.0 * into * normal[1];
 
-            for (paddle, paddle_transform) in (&paddles, &transforms).join() {
-                let paddle_x = paddle_transform.translation().x - (paddle.width * 0.5);
-                let paddle_y = paddle_transform.translation().y - (paddle.height * 0.5);
-                // create rectangle (boundary box) around paddle, check if ball's center is within here
-                if point_in_rect(
-                    ball_x,
-                    ball_y,
-                    paddle_x - ball.radius,
-                    paddle_y - ball.radius,
-                    paddle_x + paddle.width + ball.radius,
-                    paddle_y + paddle.height + ball.radius,
-                ) {
-                    if (paddle.side == Side::Left && ball.velocity[0] < 0.0)
-                        || (paddle.side == Side::Right && ball.velocity[0] > 0.0)
-                    {
-                        ball.velocity[0] = -ball.velocity[0];
-                        play_bounce_sound(
-                            &*sounds,
-                            &storage,
-                            audio_output.as_ref().map(|o| o.deref()),
-                        );
-                    }
-                }
+            if let Some(ball) = balls.get_mut(contact.a) {
+                apply_hit(
+                    &rules,
+                    ball,
+                    paddles.get_mut(contact.b),
+                    velocity,
+                    normal,
+                    other_velocity,
+                );
             }
+
+            events.single_write(match paddles.get(contact.b) {
+                Some(paddle) => GameEvent::PaddleBounce {
+                    side: paddle.side,
+                    position,
+                    speed: velocity.speed(),
+                },
+                None => GameEvent::WallBounce {
+                    position,
+                    speed: velocity.speed(),
+                },
+            });
         }
     }
 }
 
-fn point_in_rect(x: f32, y: f32, left: f32, bottom: f32, right: f32, top: f32) -> bool {
-    x >= left && x <= right && y >= bottom && y <= top
+// what a bounce does on top of mirroring the velocity: spin from the paddle or a kick off the
+// wall, and the smash boost, `normal` points out of whatever was hit
+// the rapier backend does its own bouncing and calls this for the rest
+pub fn apply_hit(
+    rules: &Rules,
+    ball: &mut Ball,
+    paddle: Option<&mut Paddle>,
+    velocity: &mut Velocity,
+    normal: [f32; 2],
+    other_velocity: [f32; 2],
+) {
+    if rules.spin.enabled {
+        let spin = &rules.spin;
+        // along the surface, anti-clockwise around the ball
+        let tangent = [-normal[1], normal[0]];
+        if paddle.is_some() {
+            // a paddle moving along its face as it hits puts spin on the ball
+            let sweep = other_velocity[0] * tangent[0] + other_velocity[1] * tangent[1];
+            ball.spin = (ball.spin + spin.transfer * sweep)
+                .max(-spin.max_spin)
+                .min(spin.max_spin);
+        } else {
+            // spin grips the wall and kicks the ball sideways, same speed, new angle
+            let speed = velocity.speed();
+            velocity.0[0] += tangent[0] * ball.spin * spin.wall_grip;
+            velocity.0[1] += tangent[1] * ball.spin * spin.wall_grip;
+            let kicked = velocity.speed();
+            if kicked > 0.0 {
+                velocity.0[0] *= speed / kicked;
+                velocity.0[1] *= speed / kicked;
+            }
+            ball.spin *= 1.0 - spin.wall_loss;
+        }
+    }
+
+    // a smash pressed just before the ball arrived sends it back faster
+    if let Some(paddle) = paddle {
+        let speed = velocity.speed();
+        if paddle.smash.is_active() && speed > 0.0 {
+            paddle.smash.active = 0.0;
+            let boosted = (speed * rules.abilities.smash_boost).min(rules.modifiers.max_speed);
+            velocity.0[0] *= boosted / speed;
+            velocity.0[1] *= boosted / speed;
+        }
+    }
 }
@@ -0,0 +1,83 @@
+use amethyst::{
+    core::timing::Time,
+    core::SystemDesc,
+    derive::SystemDesc,
+    ecs::prelude::{
+        Entities, Join, Read, ReadExpect, ReadStorage, System, SystemData, World, Write,
+        WriteStorage,
+    },
+    ui::UiText,
+};
+
+use log::warn;
+
+use crate::pong::{Ball, ScoreText};
+use crate::rules::Rules;
+use crate::scoring::{ClockPhase, MatchClock, ScoreBoard};
+
+// counts a timed match down, see MatchClock::tick for what happens when time runs out
+// uses game time and only runs while a ball is in play, so serves and pauses stop the clock
+// one of Classic's own systems, the mode reads the clock's winner
+#[derive(SystemDesc)]
+pub struct MatchClockSystem;
+
+impl<'s> System<'s> for MatchClockSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Ball>,
+        WriteStorage<'s, UiText>,
+        Write<'s, MatchClock>,
+        Write<'s, ScoreBoard>,
+        Read<'s, Rules>,
+        Read<'s, Time>,
+        Option<ReadExpect<'s, ScoreText>>, // only there while a match is running
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            balls,
+            mut ui_text,
+            mut clock,
+            mut scores,
+            rules,
+            time,
+            score_text,
+        ): Self::SystemData,
+    ) {
+        let time_limit = match rules.time_limit.as_ref() {
+            Some(time_limit) => time_limit,
+            None => return,
+        };
+        let in_play = (&balls).join().next().is_some();
+        if clock.phase == ClockPhase::Expired || scores.winner.is_some() || !in_play {
+            return;
+        }
+
+        clock.tick(time.delta_seconds(), &mut scores, &time_limit.tie_break);
+        if clock.winner.is_some() {
+            // match over, take the ball out of play
+            for (entity, _) in (&entities, &balls).join() {
+                if let Err(err) = entities.delete(entity) {
+                    warn!("Could not remove ball: {}", err);
+                }
+            }
+        }
+
+        if let Some(score_text) = score_text.as_ref() {
+            if let Some(text) = score_text
+                .clock
+                .and_then(|clock_text| ui_text.get_mut(clock_text))
+            {
+                text.text = clock.text();
+            }
+            if let Some(text) = ui_text.get_mut(score_text.status) {
+                text.text = match clock.winner {
+                    Some(winner) => format!("{:?} player wins on time!", winner),
+                    None => scores.status_text(&rules.scoring),
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use amethyst::{
+    core::{SystemDesc, Transform},
+    derive::SystemDesc,
+    ecs::prelude::{Entities, Join, ReadStorage, System, SystemData, World, Write},
+    shrev::EventChannel,
+};
+
+use crate::physics::{collide, Bounds, BroadPhase, Collider, Contact, Velocity};
+
+// rebuilds the BroadPhase grid, then runs the exact test on every pair sharing a cell where
+// at least one side moves and publishes a Contact for each overlap,
+// what to do about it is up to other systems
+#[derive(SystemDesc)]
+pub struct CollisionSystem;
+
+impl<'s> System<'s> for CollisionSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, Velocity>,
+        ReadStorage<'s, Transform>,
+        Write<'s, BroadPhase>,
+        Write<'s, EventChannel<Contact>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, colliders, velocities, transforms, mut broad_phase, mut contacts): Self::SystemData,
+    ) {
+        let bodies = (&entities, &colliders, &transforms)
+            .join()
+            .map(|(entity, collider, transform)| {
+                let position = [transform.translation().x, transform.translation().y];
+                (entity, collider, position, velocities.contains(entity))
+            })
+            .collect::<Vec<_>>();
+
+        broad_phase.clear();
+        for (index, &(_, collider, position, _)) in bodies.iter().enumerate() {
+            broad_phase.insert(index, Bounds::of(&collider.shape, position));
+        }
+
+        for (i, j) in broad_phase.pairs() {
+            let (a, collider_a, pos_a, dynamic_a) = bodies[i];
+            let (b, collider_b, pos_b, dynamic_b) = bodies[j];
+            // static things never need testing against each other
+            if !dynamic_a && !dynamic_b {
+                continue;
+            }
+            if let Some((normal, depth)) =
+                collide(&collider_a.shape, pos_a, &collider_b.shape, pos_b)
+            {
+                contacts.single_write(Contact {
+                    a,
+                    b,
+                    normal,
+                    depth,
+                });
+            }
+        }
+    }
+}
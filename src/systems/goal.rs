@@ -0,0 +1,49 @@
+use amethyst::{
+    core::transform::Transform,
+    core::SystemDesc,
+    derive::SystemDesc,
+    ecs::prelude::{Entities, Join, ReadStorage, System, SystemData, World, Write},
+    shrev::EventChannel,
+};
+
+use log::warn;
+
+use crate::events::GameEvent;
+use crate::physics::Collider;
+use crate::pong::{Ball, Goal};
+
+// spots balls going into a goal (an entity with a Goal, see initialise_goals), announces the point and takes the ball out of play,
+// what the point is worth and when the next serve comes is up to the game mode
+#[derive(SystemDesc)]
+pub struct GoalSystem;
+
+impl<'s> System<'s> for GoalSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Goal>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, Transform>,
+        Write<'s, EventChannel<GameEvent>>,
+    );
+
+    fn run(&mut self, (entities, balls, goals, colliders, locals, mut events): Self::SystemData) {
+        for (entity, _, collider, transform) in (&entities, &balls, &colliders, &locals).join() {
+            let position = [transform.translation().x, transform.translation().y];
+            let radius = collider.half_width();
+
+            let scorer = match goals.join().find(|goal| goal.reached(position, radius)) {
+                Some(goal) => goal.scorer,
+                None => continue,
+            };
+
+            events.single_write(GameEvent::Score {
+                side: scorer,
+                position,
+            });
+            if let Err(err) = entities.delete(entity) {
+                warn!("Could not remove ball: {}", err);
+            }
+        }
+    }
+}
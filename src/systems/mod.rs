@@ -1,21 +1,51 @@
 use amethyst::{
-    core::{SystemDesc, Transform},
+    core::{timing::Time, SystemDesc, Transform},
     derive::SystemDesc,
-    ecs::{Join, Read, ReadStorage, System, SystemData, World, WriteStorage},
+    ecs::{Join, Read, ReadStorage, System, SystemData, World, Write, WriteStorage},
     input::{InputHandler, StringBindings},
 };
 
 // import some stuff from pong
-use crate::pong::{Paddle, Side, ARENA_HEIGHT, PADDLE_HEIGHT};
+use crate::ai::AiPaddle;
+use crate::physics::{Collider, Velocity};
+use crate::pong::{Orientation, Paddle, Side, ARENA_HEIGHT, ARENA_WIDTH};
+use crate::rules::Rules;
 
-pub use self::bounce::BounceSystem;
+pub use self::abilities::AbilityHudSystem;
+mod abilities;
+
+pub use self::ai::AiSystem;
+mod ai;
+
+pub use self::audio::AudioSystemDesc;
+mod audio;
+
+pub use self::bounce::{apply_hit, BounceSystemDesc};
 mod bounce;
 
-pub use self::move_balls::MoveBallsSystem;
+pub use self::clock::MatchClockSystem;
+mod clock;
+
+pub use self::collision::CollisionSystem;
+mod collision;
+
+pub use self::goal::GoalSystem;
+mod goal;
+
+pub use self::modifiers::ModifierIndicatorSystem;
+mod modifiers;
+
+pub use self::music::MusicSystemDesc;
+mod music;
+
+pub use self::move_balls::{steer_ball, update_wind, MoveBallsSystem};
 mod move_balls;
 
-pub use self::winner::WinnerSystem;
-mod winner;
+pub use self::stats::MatchStatsSystemDesc;
+mod stats;
+
+pub use self::watchdog::{InputLog, InputSample, WatchdogSystem};
+mod watchdog;
 
 // System Descriptor unit struct
 // Systems must have SystemDesc trait to specify logic for System instantiation
@@ -25,36 +55,117 @@ pub struct PaddleSystem;
 impl<'s> System<'s> for PaddleSystem {
     // data system operates on
     // describes what kind of world resources are required to power the system
-    // Mutates Transform components
-    // Reads Paddle components
+    // Mutates Velocity components (the MoveBallsSystem does the actual moving)
+    // Mutates Paddle components too, their dash and smash timers
+    // Reads Collider and Transform components, skips paddles the computer is playing
     // also assesses InputHandler resource
     type SystemData = (
-        WriteStorage<'s, Transform>,
-        ReadStorage<'s, Paddle>,
+        WriteStorage<'s, Velocity>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, Paddle>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, AiPaddle>,
         Read<'s, InputHandler<StringBindings>>,
+        Read<'s, Time>,
+        Read<'s, Rules>,
+        Write<'s, InputLog>,
     );
 
-    fn run(&mut self, (mut transforms, paddles, input): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            mut velocities,
+            transforms,
+            mut paddles,
+            colliders,
+            ais,
+            input,
+            time,
+            rules,
+            mut input_log,
+        ): Self::SystemData,
+    ) {
         // unpack the SystemData object
-        for (paddle, transform) in (&paddles, &mut transforms).join() {
-            // performs join over Transform and Paddle storages
-            // iterates over all entities that have both a Paddle and Transform attached to them
-            // while giving mutable access to Transform, immutable access to Paddle
+        if time.delta_seconds() > 0.0 {
+            // kept for the watchdog's reports
+            input_log.record(InputSample {
+                frame: time.frame_number(),
+                left: input.axis_value("left_paddle"),
+                right: input.axis_value("right_paddle"),
+            });
+        }
+        for (paddle, collider, transform, velocity, _) in (
+            &mut paddles,
+            &colliders,
+            &transforms,
+            &mut velocities,
+            !&ais,
+        )
+            .join()
+        {
+            // performs join over the Paddle, Collider, Transform and Velocity storages
+            // iterates over all entities that have all of them attached
+            // while giving mutable access to Paddle and Velocity, immutable access to the rest
             // possible to use par_join for multi threaded, but not worth overhead here
-            let movement = match paddle.side {
-                Side::Left => input.axis_value("left_paddle"),
-                Side::Right => input.axis_value("right_paddle"),
+            let (axis, dash, smash) = match paddle.side {
+                Side::Left => ("left_paddle", "left_dash", "left_smash"),
+                Side::Right => ("right_paddle", "right_dash", "right_smash"),
+            };
+            // a paddle along the bottom is steered left and right instead
+            let axis = match paddle.orientation {
+                Orientation::Vertical => axis,
+                Orientation::Horizontal => "horizontal_paddle",
             };
-            // if there is change for the paddle, apply the transform for it
+            let movement = input.axis_value(axis);
+            let delta = time.delta_seconds();
+            velocity.0 = [0.0, 0.0];
+            if delta <= 0.0 {
+                continue; // paused, cooldowns don't run either
+            }
+
+            let abilities = &rules.abilities;
+            paddle.dash.tick(delta);
+            paddle.smash.tick(delta);
+            paddle.dash.trigger(
+                input.action_is_down(dash).unwrap_or(false),
+                abilities.dash_seconds,
+                abilities.dash_cooldown,
+            );
+            // the BounceSystem checks for an active smash when the ball arrives
+            paddle.smash.trigger(
+                input.action_is_down(smash).unwrap_or(false),
+                abilities.smash_window,
+                abilities.smash_cooldown,
+            );
+            let speed = if paddle.dash.is_active() {
+                paddle.speed * abilities.dash_speed
+            } else {
+                paddle.speed
+            };
+
+            // if there is change for the paddle, work out the velocity that gets it there this frame
             if let Some(mv_amount) = movement {
-                let scaled_amount = 1.2 * mv_amount as f32;
-                let paddle_y = transform.translation().y;
-                transform.set_translation_y(
-                    (paddle_y + scaled_amount)
-                        .min(ARENA_HEIGHT - PADDLE_HEIGHT * 0.5)
-                        .max(PADDLE_HEIGHT * 0.5),
-                ); // clamps the paddle within arena boundaries
-            } // would usually use amethyse::core::timing::Time to get time diff between frames for consistency
+                // scaled by frame time, so paddles also freeze when the game is paused
+                let scaled_amount = speed * delta * mv_amount as f32;
+                let (along, position, half_length, arena_length) = match paddle.orientation {
+                    Orientation::Vertical => (
+                        1,
+                        transform.translation().y,
+                        collider.half_height(),
+                        ARENA_HEIGHT,
+                    ),
+                    Orientation::Horizontal => (
+                        0,
+                        transform.translation().x,
+                        collider.half_width(),
+                        ARENA_WIDTH,
+                    ),
+                };
+                let target = (position + scaled_amount)
+                    .min(arena_length - half_length)
+                    .max(half_length); // clamps the paddle within arena boundaries
+                velocity.0[along] = (target - position) / delta;
+            }
         }
     }
 }
@@ -0,0 +1,57 @@
+use amethyst::{
+    core::SystemDesc,
+    derive::SystemDesc,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, World, WriteStorage},
+    ui::UiText,
+};
+
+use crate::modifiers::{ModifierIndicator, WindState};
+use crate::rules::Rules;
+
+// keeps the gravity and wind readout at the bottom of the arena up to date
+#[derive(SystemDesc)]
+pub struct ModifierIndicatorSystem;
+
+impl<'s> System<'s> for ModifierIndicatorSystem {
+    type SystemData = (
+        ReadStorage<'s, ModifierIndicator>,
+        WriteStorage<'s, UiText>,
+        Read<'s, Rules>,
+        Read<'s, WindState>,
+    );
+
+    fn run(&mut self, (indicators, mut texts, rules, wind): Self::SystemData) {
+        for (_, text) in (&indicators, &mut texts).join() {
+            let mut parts = Vec::new();
+            let gravity = rules.modifiers.gravity;
+            if gravity != [0.0, 0.0] {
+                parts.push(format!("gravity {}", describe(gravity)));
+            }
+            if rules.modifiers.wind.is_some() {
+                parts.push(if wind.force == [0.0, 0.0] {
+                    "wind calm".to_string()
+                } else {
+                    format!("wind {}", describe(wind.force))
+                });
+            }
+            text.text = parts.join("   ");
+        }
+    }
+}
+
+// rough direction arrow and strength, e.g. "> 12"
+fn describe(force: [f32; 2]) -> String {
+    let arrow = if force[0].abs() >= force[1].abs() {
+        if force[0] < 0.0 {
+            "<"
+        } else {
+            ">"
+        }
+    } else if force[1] < 0.0 {
+        "v"
+    } else {
+        "^"
+    };
+    let strength = (force[0] * force[0] + force[1] * force[1]).sqrt();
+    format!("{} {:.0}", arrow, strength)
+}
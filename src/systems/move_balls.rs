@@ -3,27 +3,189 @@ use amethyst::{
     core::transform::Transform,
     core::SystemDesc,
     derive::SystemDesc,
-    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, World, WriteStorage},
+    ecs::prelude::{Join, Read, System, SystemData, World, Write, WriteStorage},
 };
 
+use crate::modifiers::{Modifiers, WindState, ZoneEffect};
+use crate::physics::Velocity;
 use crate::pong::Ball;
+use crate::rules::{Rules, SpinRules};
 
+// integrates every Velocity, not just balls (paddles set theirs in the PaddleSystem)
+// balls are also pushed around by the match modifiers (gravity, wind, zones) and curve when
+// they spin, everything only depends on the frame time so replays come out the same
 #[derive(SystemDesc)]
 pub struct MoveBallsSystem;
 
 impl<'s> System<'s> for MoveBallsSystem {
     type SystemData = (
-        ReadStorage<'s, Ball>,
+        WriteStorage<'s, Ball>,
+        WriteStorage<'s, Velocity>,
         WriteStorage<'s, Transform>,
+        Read<'s, Rules>,
+        Write<'s, WindState>,
         Read<'s, Time>,
     );
 
-    fn run(&mut self, (balls, mut locals, time): Self::SystemData) {
-        for (ball, local) in (&balls, &mut locals).join() {
+    fn run(
+        &mut self,
+        (mut balls, mut velocities, mut locals, rules, mut wind, time): Self::SystemData,
+    ) {
+        let delta = time.delta_seconds();
+        update_wind(&mut wind, &rules.modifiers, delta);
+
+        for (ball, velocity, local) in (&mut balls, &mut velocities, &locals).join() {
+            let position = [local.translation().x, local.translation().y];
+            steer_ball(&rules, wind.force, ball, position, velocity, delta);
+        }
+
+        for (velocity, local) in (&velocities, &mut locals).join() {
             // for each ball, mutate it's location according to its current vectors
-            local.prepend_translation_x(ball.velocity[0] * time.delta_seconds());
-            local.prepend_translation_y(ball.velocity[1] * time.delta_seconds());
+            local.prepend_translation_x(velocity.0[0] * delta);
+            local.prepend_translation_y(velocity.0[1] * delta);
             // delta time is used here, gets duration since last frame (which accounts for actual time difference)
         }
     }
 }
+
+// moves the wind on by a frame, the rapier backend does this too
+pub fn update_wind(wind: &mut WindState, modifiers: &Modifiers, delta: f32) {
+    wind.clock += delta;
+    wind.force = modifiers
+        .wind
+        .as_ref()
+        .map_or([0.0, 0.0], |gusts| gusts.force_at(wind.clock));
+}
+
+// everything that changes a ball's velocity in flight, also used by the rapier backend
+// before it hands the velocity to the ball's rigid body
+pub fn steer_ball(
+    rules: &Rules,
+    wind: [f32; 2],
+    ball: &mut Ball,
+    position: [f32; 2],
+    velocity: &mut Velocity,
+    delta: f32,
+) {
+    if !rules.modifiers.is_empty() {
+        apply_modifiers(&rules.modifiers, wind, position, velocity, delta);
+    }
+    if rules.spin.enabled {
+        apply_spin(&rules.spin, ball, velocity, delta);
+    }
+}
+
+fn apply_modifiers(
+    modifiers: &Modifiers,
+    wind: [f32; 2],
+    position: [f32; 2],
+    velocity: &mut Velocity,
+    delta: f32,
+) {
+    let mut push = [
+        modifiers.gravity[0] + wind[0],
+        modifiers.gravity[1] + wind[1],
+    ];
+    let mut scale = 1.0;
+    for zone in modifiers
+        .zones
+        .iter()
+        .filter(|zone| zone.contains(position))
+    {
+        match zone.effect {
+            ZoneEffect::Slow(rate) => scale *= (1.0 - rate * delta).max(0.0),
+            ZoneEffect::Speed(rate) => scale *= 1.0 + rate * delta,
+            ZoneEffect::Deflect(force) => {
+                push[0] += force[0];
+                push[1] += force[1];
+            }
+        }
+    }
+
+    velocity.0[0] = (velocity.0[0] + push[0] * delta) * scale;
+    velocity.0[1] = (velocity.0[1] + push[1] * delta) * scale;
+    let speed = velocity.speed();
+    if speed > modifiers.max_speed {
+        velocity.0[0] *= modifiers.max_speed / speed;
+        velocity.0[1] *= modifiers.max_speed / speed;
+    }
+}
+
+// turns the velocity by an angle proportional to the spin, which bends the path
+// without changing the speed, and lets the spin slowly die off
+fn apply_spin(spin: &SpinRules, ball: &mut Ball, velocity: &mut Velocity, delta: f32) {
+    let angle = spin.curve * ball.spin * delta;
+    let (sin, cos) = angle.sin_cos();
+    let [x, y] = velocity.0;
+    velocity.0 = [x * cos - y * sin, x * sin + y * cos];
+    ball.spin *= (1.0 - spin.decay * delta).max(0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifiers::Zone;
+
+    fn with_zone(effect: ZoneEffect) -> Modifiers {
+        Modifiers {
+            zones: vec![Zone {
+                min: [40.0, 30.0],
+                max: [60.0, 70.0],
+                effect,
+            }],
+            ..Modifiers::default()
+        }
+    }
+
+    fn pushed(modifiers: &Modifiers, position: [f32; 2], delta: f32) -> [f32; 2] {
+        let mut velocity = Velocity([50.0, 0.0]);
+        apply_modifiers(modifiers, [0.0, 0.0], position, &mut velocity, delta);
+        velocity.0
+    }
+
+    fn assert_close(actual: [f32; 2], expected: [f32; 2]) {
+        for axis in 0..2 {
+            assert!(
+                (actual[axis] - expected[axis]).abs() < 1e-4,
+                "velocity {:?}, expected {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn zones_act_on_balls_inside_and_on_the_edge_only() {
+        let slow = with_zone(ZoneEffect::Slow(0.5));
+        assert_close(pushed(&slow, [50.0, 50.0], 0.1), [47.5, 0.0]);
+        assert_close(pushed(&slow, [60.0, 70.0], 0.1), [47.5, 0.0]);
+        assert_close(pushed(&slow, [60.5, 50.0], 0.1), [50.0, 0.0]);
+    }
+
+    #[test]
+    fn zone_effects_scale_with_the_frame_time() {
+        let speed = with_zone(ZoneEffect::Speed(0.5));
+        assert_close(pushed(&speed, [50.0, 50.0], 0.1), [52.5, 0.0]);
+        let deflect = with_zone(ZoneEffect::Deflect([0.0, 20.0]));
+        assert_close(pushed(&deflect, [50.0, 50.0], 0.5), [50.0, 10.0]);
+        // a slow zone can stop a ball in a long frame, but never turns it around
+        let slow = with_zone(ZoneEffect::Slow(2.0));
+        assert_close(pushed(&slow, [50.0, 50.0], 1.0), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn gravity_and_wind_add_up_and_speed_is_capped() {
+        let modifiers = Modifiers {
+            gravity: [0.0, -10.0],
+            max_speed: 60.0,
+            ..Modifiers::default()
+        };
+        let mut velocity = Velocity([0.0, 0.0]);
+        apply_modifiers(&modifiers, [4.0, 0.0], [0.0, 0.0], &mut velocity, 0.5);
+        assert_close(velocity.0, [2.0, -5.0]);
+
+        let mut velocity = Velocity([100.0, 0.0]);
+        apply_modifiers(&modifiers, [0.0, 0.0], [0.0, 0.0], &mut velocity, 0.0);
+        assert_close(velocity.0, [60.0, 0.0]);
+    }
+}
@@ -0,0 +1,267 @@
+use amethyst::{
+    assets::AssetStorage,
+    audio::{Source, SourceHandle},
+    core::{timing::Time, SystemDesc},
+    ecs::prelude::{Read, System, SystemData, World, Write},
+    input::{InputHandler, StringBindings},
+    shrev::{EventChannel, ReaderId},
+};
+
+use log::warn;
+use rand::seq::SliceRandom;
+use rodio::{Decoder, Device, Sink, Source as _};
+
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::audio::{AudioDevice, AudioMixer, Music, Playlist};
+use crate::events::GameEvent;
+
+#[derive(Default)]
+pub struct MusicSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, MusicSystem> for MusicSystemDesc {
+    fn build(self, world: &mut World) -> MusicSystem {
+        <MusicSystem as System<'_>>::SystemData::setup(world);
+        let reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        MusicSystem {
+            reader,
+            lengths: HashMap::new(),
+            playing: None,
+            fading_out: Vec::new(),
+            playlist: None,
+            order: Vec::new(),
+            position: 0,
+            duck_timer: 0.0,
+            next_held: false,
+            shuffle_held: false,
+        }
+    }
+}
+
+// one track being played on its own sink, so two can overlap during a crossfade
+struct Track {
+    sink: Sink,
+    gain: f32,    // fade level, 0 silent .. 1 full
+    elapsed: f32, // seconds played so far
+    length: TrackLength,
+}
+
+// filled in by a background thread once the track is measured
+type TrackLength = Arc<Mutex<Option<f32>>>;
+
+impl Track {
+    fn remaining(&self) -> Option<f32> {
+        self.length
+            .lock()
+            .ok()
+            .and_then(|length| *length)
+            .map(|length| length - self.elapsed)
+    }
+}
+
+// replaces the DjSystem: plays the active playlist, crossfades between tracks,
+// handles skip/shuffle input and ducks the music for a moment when a point is scored
+pub struct MusicSystem {
+    reader: ReaderId<GameEvent>,
+    lengths: HashMap<u32, TrackLength>, // by asset id, each track is only measured once
+    playing: Option<Track>,
+    fading_out: Vec<Track>,
+    playlist: Option<Playlist>, // playlist the current order was built from
+    order: Vec<usize>,          // track indices in play order (shuffled or not)
+    position: usize,            // index into `order` of the next track to start
+    duck_timer: f32,
+    next_held: bool,
+    shuffle_held: bool,
+}
+
+impl<'s> System<'s> for MusicSystem {
+    type SystemData = (
+        Read<'s, EventChannel<GameEvent>>,
+        Read<'s, AssetStorage<Source>>,
+        Option<Write<'s, Music>>,
+        Read<'s, AudioMixer>,
+        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, Time>,
+        Option<Read<'s, AudioDevice>>,
+    );
+
+    fn run(&mut self, (events, storage, music, mixer, input, time, device): Self::SystemData) {
+        // music keeps going while the game is paused, so use real time
+        let delta = time.delta_real_seconds();
+
+        let (mut music, device) = match (music, device) {
+            (Some(music), Some(device)) => (music, device),
+            _ => {
+                // nothing loaded yet or nothing to play on, just keep the reader up to date
+                events.read(&mut self.reader).for_each(drop);
+                return;
+            }
+        };
+
+        for event in events.read(&mut self.reader) {
+            if let GameEvent::Score { .. } = event {
+                self.duck_timer = music.duck_seconds;
+            }
+        }
+        self.duck_timer = (self.duck_timer - delta).max(0.0);
+
+        // only react to the key going down, not to it being held
+        let next_down = input.action_is_down("next_track").unwrap_or(false);
+        let skip = next_down && !self.next_held;
+        self.next_held = next_down;
+
+        let shuffle_down = input.action_is_down("shuffle_music").unwrap_or(false);
+        if shuffle_down && !self.shuffle_held {
+            music.shuffle = !music.shuffle;
+            self.build_order(&music);
+        }
+        self.shuffle_held = shuffle_down;
+
+        if self.playlist != Some(music.current) {
+            self.build_order(&music);
+            self.start_next(&device.0, &music, &storage);
+        } else if skip || self.track_ending(music.crossfade) {
+            self.start_next(&device.0, &music, &storage);
+        }
+
+        self.update_volumes(delta, &music, &mixer);
+    }
+}
+
+impl MusicSystem {
+    fn build_order(&mut self, music: &Music) {
+        self.playlist = Some(music.current);
+        self.order = (0..music.tracks(music.current).len()).collect();
+        if music.shuffle {
+            self.order.shuffle(&mut rand::thread_rng());
+        }
+        self.position = 0;
+    }
+
+    fn track_ending(&self, crossfade: f32) -> bool {
+        match self.playing {
+            Some(ref track) => {
+                track.sink.empty() || track.remaining().map_or(false, |left| left <= crossfade)
+            }
+            // nothing playing (e.g. the first track wasn't loaded yet), try again
+            None => true,
+        }
+    }
+
+    // fades the current track out and starts the next one in the playlist
+    fn start_next(&mut self, device: &Device, music: &Music, storage: &AssetStorage<Source>) {
+        let tracks = music.tracks(music.current);
+        self.measure(tracks, storage);
+        if self.order.is_empty() {
+            self.fade_out_current();
+            return;
+        }
+        if self.position >= self.order.len() {
+            // played through the list, go around again (reshuffling if needed)
+            self.build_order(music);
+        }
+
+        let handle = &tracks[self.order[self.position]];
+        let source = match storage.get(handle) {
+            Some(source) => source,
+            None => return, // still loading, try again next frame
+        };
+        self.position += 1;
+
+        let decoder = match Decoder::new(Cursor::new(source.bytes.clone())) {
+            Ok(decoder) => decoder,
+            Err(err) => {
+                warn!("Could not decode music track: {}", err);
+                return;
+            }
+        };
+
+        self.fade_out_current();
+        let sink = Sink::new(device);
+        sink.set_volume(0.0);
+        sink.append(decoder);
+        self.playing = Some(Track {
+            sink,
+            gain: 0.0,
+            elapsed: 0.0,
+            length: self.lengths.get(&handle.id()).cloned().unwrap_or_default(),
+        });
+    }
+
+    // starts measuring every loaded track of the playlist that hasn't been measured yet
+    fn measure(&mut self, tracks: &[SourceHandle], storage: &AssetStorage<Source>) {
+        for handle in tracks {
+            if self.lengths.contains_key(&handle.id()) {
+                continue;
+            }
+            if let Some(source) = storage.get(handle) {
+                self.lengths
+                    .insert(handle.id(), measure_length(source.bytes.clone()));
+            }
+        }
+    }
+
+    fn fade_out_current(&mut self) {
+        if let Some(track) = self.playing.take() {
+            self.fading_out.push(track);
+        }
+    }
+
+    fn update_volumes(&mut self, delta: f32, music: &Music, mixer: &AudioMixer) {
+        let step = if music.crossfade > 0.0 {
+            delta / music.crossfade
+        } else {
+            1.0
+        };
+        let duck = if self.duck_timer > 0.0 {
+            music.duck_volume
+        } else {
+            1.0
+        };
+        let volume = mixer.music_volume() * duck;
+
+        if let Some(ref mut track) = self.playing {
+            track.gain = (track.gain + step).min(1.0);
+            track.elapsed += delta;
+            track.sink.set_volume(track.gain * volume);
+        }
+        for track in &mut self.fading_out {
+            track.gain = (track.gain - step).max(0.0);
+            track.elapsed += delta;
+            track.sink.set_volume(track.gain * volume);
+        }
+        // dropping the sink stops the track
+        self.fading_out
+            .retain(|track| track.gain > 0.0 && !track.sink.empty());
+    }
+}
+
+// most formats say how long they are, rodio can't tell for an ogg up front,
+// so those are decoded once off the main thread
+fn measure_length(bytes: Vec<u8>) -> TrackLength {
+    let length = Arc::new(Mutex::new(None));
+    let result = length.clone();
+    thread::spawn(move || {
+        if let Ok(decoder) = Decoder::new(Cursor::new(bytes)) {
+            let seconds = match decoder.total_duration() {
+                Some(duration) => duration.as_secs_f32(),
+                None => {
+                    let samples_per_second =
+                        decoder.sample_rate() as f32 * decoder.channels() as f32;
+                    decoder.count() as f32 / samples_per_second
+                }
+            };
+            if let Ok(mut length) = result.lock() {
+                *length = Some(seconds);
+            }
+        }
+    });
+    length
+}
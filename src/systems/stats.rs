@@ -0,0 +1,59 @@
+use amethyst::{
+    core::{timing::Time, SystemDesc},
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, World, Write},
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::events::GameEvent;
+use crate::pong::{Ball, Side};
+use crate::stats::MatchStats;
+
+// the event reader has to be registered on the channel, so this one needs a hand written descriptor
+#[derive(Default)]
+pub struct MatchStatsSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, MatchStatsSystem> for MatchStatsSystemDesc {
+    fn build(self, world: &mut World) -> MatchStatsSystem {
+        <MatchStatsSystem as System<'_>>::SystemData::setup(world);
+        let reader = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        MatchStatsSystem { reader }
+    }
+}
+
+// counts rallies, goals and playing time into the MatchStats, the match resets them when it starts
+pub struct MatchStatsSystem {
+    reader: ReaderId<GameEvent>,
+}
+
+impl<'s> System<'s> for MatchStatsSystem {
+    type SystemData = (
+        Read<'s, EventChannel<GameEvent>>,
+        ReadStorage<'s, Ball>,
+        Read<'s, Time>,
+        Write<'s, MatchStats>,
+    );
+
+    fn run(&mut self, (events, balls, time, mut stats): Self::SystemData) {
+        // like the match clock, time between points doesn't count
+        if (&balls).join().next().is_some() {
+            stats.duration += time.delta_seconds();
+        }
+        for event in events.read(&mut self.reader) {
+            match event {
+                GameEvent::PaddleBounce { .. } => stats.rally += 1,
+                GameEvent::Score { side, .. } => {
+                    let rally = stats.rally;
+                    stats.rallies.push(rally);
+                    stats.rally = 0;
+                    match side {
+                        Side::Left => stats.goals[0] += 1,
+                        Side::Right => stats.goals[1] += 1,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use amethyst::{
+    core::{timing::Time, Transform},
+    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, System, Write},
+    shrev::EventChannel,
+};
+
+use log::warn;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+
+use crate::events::GameEvent;
+use crate::physics::Velocity;
+use crate::pong::{Ball, Orientation, Paddle, ARENA_HEIGHT, ARENA_WIDTH};
+
+// how many frames of input are kept for the report
+const INPUT_LOG_FRAMES: usize = 120;
+// a ball this far outside the arena has escaped, the GoalSystem takes it out of play well before that
+const ARENA_MARGIN: f32 = 10.0;
+// a ball barely moving (or not moving towards the paddles) for this many ticks is stuck
+const STUCK_TICKS: u32 = 120;
+const STUCK_DISTANCE: f32 = 0.01;
+const MIN_CROSSING_SPEED: f32 = 1.0;
+
+// one frame of paddle input, written by the PaddleSystem
+#[derive(Debug, Clone, Copy)]
+pub struct InputSample {
+    pub frame: u64,
+    pub left: Option<f64>,
+    pub right: Option<f64>,
+}
+
+// the last few frames of paddle input, so a bad state can be traced back to what the players did
+#[derive(Default)]
+pub struct InputLog {
+    samples: VecDeque<InputSample>,
+}
+
+impl InputLog {
+    pub fn record(&mut self, sample: InputSample) {
+        if self.samples.len() == INPUT_LOG_FRAMES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &InputSample> {
+        self.samples.iter()
+    }
+}
+
+// what's wrong with a ball
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Problem {
+    NotFinite,
+    OutOfArena,
+    NotCrossing, // moving along the paddles but never towards them
+    Stuck,
+}
+
+#[derive(Default)]
+struct Tracker {
+    last_position: [f32; 2],
+    still_ticks: u32,
+    parallel_ticks: u32,
+}
+
+// last line of defence against the simulation going wrong: big frame deltas, NaNs creeping in,
+// a ball wedged inside a paddle or bouncing between two walls forever without reaching a paddle
+// a ball in a bad state is taken out of play and a report is logged, the mode is told
+// with a BallRemoved event and serves a new one the way it always does
+#[derive(Default)]
+pub struct WatchdogSystem {
+    trackers: HashMap<Entity, Tracker>,
+}
+
+impl<'s> System<'s> for WatchdogSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, Velocity>,
+        ReadStorage<'s, Transform>,
+        Read<'s, InputLog>,
+        Read<'s, Time>,
+        Write<'s, EventChannel<GameEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            balls,
+            paddles,
+            velocities,
+            transforms,
+            input_log,
+            time,
+            mut events,
+        ): Self::SystemData,
+    ) {
+        if time.delta_seconds() <= 0.0 {
+            return; // paused, nothing moves so nothing can be stuck
+        }
+        self.trackers
+            .retain(|entity, _| entities.is_alive(*entity) && balls.contains(*entity));
+        // a mode's paddles all slide the same way, that decides which way the ball has to travel
+        let orientation = (&paddles).join().next().map(|paddle| paddle.orientation);
+
+        for (entity, _, velocity, transform) in (&entities, &balls, &velocities, &transforms).join()
+        {
+            let position = [transform.translation().x, transform.translation().y];
+            let tracker = self.trackers.entry(entity).or_insert_with(|| Tracker {
+                last_position: position,
+                ..Tracker::default()
+            });
+            let problem = check(tracker, position, velocity.0, orientation);
+            tracker.last_position = position;
+            let problem = match problem {
+                Some(problem) => problem,
+                None => continue,
+            };
+
+            warn!(
+                "{}",
+                report(
+                    problem,
+                    time.frame_number(),
+                    position,
+                    velocity.0,
+                    tracker,
+                    &input_log
+                )
+            );
+
+            if let Err(err) = entities.delete(entity) {
+                warn!("Could not remove broken ball: {}", err);
+                continue;
+            }
+            events.single_write(GameEvent::BallRemoved);
+        }
+    }
+}
+
+// `orientation` is how the paddles slide, the ball has to keep crossing the arena the other way
+// (sideways for vertical paddles, up and down for breakout's); None when there are no paddles
+fn check(
+    tracker: &mut Tracker,
+    position: [f32; 2],
+    velocity: [f32; 2],
+    orientation: Option<Orientation>,
+) -> Option<Problem> {
+    if position
+        .iter()
+        .chain(velocity.iter())
+        .any(|v| !v.is_finite())
+    {
+        return Some(Problem::NotFinite);
+    }
+    if position[0] < -ARENA_MARGIN
+        || position[0] > ARENA_WIDTH + ARENA_MARGIN
+        || position[1] < -ARENA_MARGIN
+        || position[1] > ARENA_HEIGHT + ARENA_MARGIN
+    {
+        return Some(Problem::OutOfArena);
+    }
+
+    // a corner hit can leave it running along the paddles for a moment, only complain if it lasts
+    let crossing = match orientation {
+        Some(Orientation::Vertical) => velocity[0],
+        Some(Orientation::Horizontal) => velocity[1],
+        None => MIN_CROSSING_SPEED,
+    };
+    if crossing.abs() < MIN_CROSSING_SPEED {
+        tracker.parallel_ticks += 1;
+    } else {
+        tracker.parallel_ticks = 0;
+    }
+    let moved = (position[0] - tracker.last_position[0]).abs()
+        + (position[1] - tracker.last_position[1]).abs();
+    if moved < STUCK_DISTANCE {
+        tracker.still_ticks += 1;
+    } else {
+        tracker.still_ticks = 0;
+    }
+
+    if tracker.still_ticks >= STUCK_TICKS {
+        Some(Problem::Stuck)
+    } else if tracker.parallel_ticks >= STUCK_TICKS {
+        Some(Problem::NotCrossing)
+    } else {
+        None
+    }
+}
+
+fn report(
+    problem: Problem,
+    frame: u64,
+    position: [f32; 2],
+    velocity: [f32; 2],
+    tracker: &Tracker,
+    input_log: &InputLog,
+) -> String {
+    let mut report = format!(
+        "Watchdog respawned a ball at frame {}: {:?}\n  position: {:?}\n  velocity: {:?}\n  \
+         ticks without moving: {}, ticks without crossing: {}\n  last inputs (frame: left, right):",
+        frame, problem, position, velocity, tracker.still_ticks, tracker.parallel_ticks
+    );
+    for sample in input_log.samples() {
+        let _ = write!(
+            report,
+            "\n    {}: {:?}, {:?}",
+            sample.frame, sample.left, sample.right
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIDDLE: [f32; 2] = [ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5];
+    const SERVE: [f32; 2] = [75.0, 50.0];
+    const PONG: Option<Orientation> = Some(Orientation::Vertical);
+    const BREAKOUT: Option<Orientation> = Some(Orientation::Horizontal);
+
+    fn tracker_at(position: [f32; 2]) -> Tracker {
+        Tracker {
+            last_position: position,
+            ..Tracker::default()
+        }
+    }
+
+    // runs `check` for `ticks` frames of 10ms of pong, moving by `velocity` each one
+    fn run(tracker: &mut Tracker, velocity: [f32; 2], ticks: u32) -> Option<Problem> {
+        run_with(tracker, velocity, ticks, PONG)
+    }
+
+    fn run_with(
+        tracker: &mut Tracker,
+        velocity: [f32; 2],
+        ticks: u32,
+        orientation: Option<Orientation>,
+    ) -> Option<Problem> {
+        let mut problem = None;
+        for _ in 0..ticks {
+            let position = [
+                tracker.last_position[0] + velocity[0] * 0.01,
+                tracker.last_position[1] + velocity[1] * 0.01,
+            ];
+            problem = check(tracker, position, velocity, orientation);
+            tracker.last_position = position;
+        }
+        problem
+    }
+
+    #[test]
+    fn nan_anywhere_is_not_finite() {
+        let mut tracker = tracker_at(MIDDLE);
+        let nan = std::f32::NAN;
+        assert_eq!(
+            check(&mut tracker, [nan, MIDDLE[1]], SERVE, PONG),
+            Some(Problem::NotFinite)
+        );
+        assert_eq!(
+            check(&mut tracker, MIDDLE, [SERVE[0], std::f32::INFINITY], PONG),
+            Some(Problem::NotFinite)
+        );
+    }
+
+    #[test]
+    fn only_balls_beyond_the_margin_are_out_of_the_arena() {
+        let mut tracker = tracker_at(MIDDLE);
+        let edge = [-ARENA_MARGIN, ARENA_HEIGHT + ARENA_MARGIN];
+        assert_eq!(check(&mut tracker, edge, SERVE, PONG), None);
+        let beyond = [-ARENA_MARGIN - 0.1, MIDDLE[1]];
+        assert_eq!(
+            check(&mut tracker, beyond, SERVE, PONG),
+            Some(Problem::OutOfArena)
+        );
+        let above = [MIDDLE[0], ARENA_HEIGHT + ARENA_MARGIN + 0.1];
+        assert_eq!(
+            check(&mut tracker, above, SERVE, PONG),
+            Some(Problem::OutOfArena)
+        );
+    }
+
+    #[test]
+    fn a_ball_standing_still_is_stuck_after_stuck_ticks() {
+        let mut tracker = tracker_at(MIDDLE);
+        assert_eq!(run(&mut tracker, [0.0, 0.0], STUCK_TICKS - 1), None);
+        assert_eq!(tracker.still_ticks, STUCK_TICKS - 1);
+        assert_eq!(run(&mut tracker, [0.0, 0.0], 1), Some(Problem::Stuck));
+    }
+
+    #[test]
+    fn a_ball_going_straight_up_and_down_never_crosses_to_vertical_paddles() {
+        let mut tracker = tracker_at(MIDDLE);
+        let vertical = [MIN_CROSSING_SPEED * 0.5, 40.0];
+        assert_eq!(run(&mut tracker, vertical, STUCK_TICKS - 1), None);
+        assert_eq!(tracker.still_ticks, 0);
+        assert_eq!(run(&mut tracker, vertical, 1), Some(Problem::NotCrossing));
+    }
+
+    #[test]
+    fn counters_reset_once_the_ball_moves_again() {
+        let mut tracker = tracker_at(MIDDLE);
+        assert_eq!(run(&mut tracker, [0.0, 0.0], STUCK_TICKS - 1), None);
+        assert_eq!(run(&mut tracker, SERVE, 1), None);
+        assert_eq!(tracker.still_ticks, 0);
+        assert_eq!(tracker.parallel_ticks, 0);
+        // it takes the full count again before it's stuck
+        assert_eq!(run(&mut tracker, [0.0, 0.0], STUCK_TICKS - 1), None);
+        assert_eq!(run(&mut tracker, [0.0, 0.0], 1), Some(Problem::Stuck));
+    }
+
+    #[test]
+    fn a_breakout_ball_going_straight_up_and_down_is_a_rally() {
+        let mut tracker = tracker_at(MIDDLE);
+        let vertical = [0.0, 40.0];
+        assert_eq!(
+            run_with(&mut tracker, vertical, STUCK_TICKS + 10, BREAKOUT),
+            None
+        );
+        assert_eq!(tracker.parallel_ticks, 0);
+    }
+
+    #[test]
+    fn a_breakout_ball_going_side_to_side_never_crosses_to_the_paddle() {
+        let mut tracker = tracker_at(MIDDLE);
+        let sideways = [40.0, MIN_CROSSING_SPEED * 0.5];
+        assert_eq!(
+            run_with(&mut tracker, sideways, STUCK_TICKS - 1, BREAKOUT),
+            None
+        );
+        assert_eq!(
+            run_with(&mut tracker, sideways, 1, BREAKOUT),
+            Some(Problem::NotCrossing)
+        );
+    }
+
+    #[test]
+    fn without_paddles_only_a_ball_that_stops_is_stuck() {
+        let mut tracker = tracker_at(MIDDLE);
+        assert_eq!(
+            run_with(&mut tracker, [0.0, 40.0], STUCK_TICKS + 10, None),
+            None
+        );
+        assert_eq!(
+            run_with(&mut tracker, [40.0, 0.0], STUCK_TICKS + 10, None),
+            None
+        );
+        assert_eq!(
+            run_with(&mut tracker, [0.0, 0.0], STUCK_TICKS, None),
+            Some(Problem::Stuck)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use amethyst::{
+    ecs::prelude::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, LineMode, UiText, UiTransform},
+};
+
+use log::warn;
+
+use super::{save_tournament, Tournament, TournamentSetup};
+use crate::handicap::Handicaps;
+use crate::loading::GameAssets;
+use crate::modes::Classic;
+use crate::pong::{Pong, Side};
+use crate::profiles::{Keys, Player, Players};
+use crate::rules::Rules;
+use crate::scoring::Scoring;
+use crate::stats::MatchStats;
+
+const TEXT_COLOR: [f32; 4] = [1., 1., 1., 1.];
+
+// shows the bracket (or the round robin table) between matches and plays the next one through
+// the Pong state pushed on top; it pops itself once the match is won, and the result is read
+// back from the MatchStats; matches left before they're won don't count and are played again
+pub struct BracketScreen {
+    tournament: Tournament,
+    playing: Option<(usize, usize)>, // round and fixture of the match on top of us
+    scoring: Option<Scoring>,        // the rules' own scoring while a match plays to `points`
+    entities: Vec<Entity>,
+}
+
+impl BracketScreen {
+    pub fn new(tournament: Tournament) -> Self {
+        BracketScreen {
+            tournament,
+            playing: None,
+            scoring: None,
+            entities: Vec::new(),
+        }
+    }
+}
+
+impl SimpleState for BracketScreen {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    // UI is taken down while a match is on top, and rebuilt when we're back
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        if let Some(scoring) = self.scoring.take() {
+            world.write_resource::<Rules>().scoring = scoring;
+        }
+        if let Some((round, index)) = self.playing.take() {
+            let stats = world.read_resource::<MatchStats>().clone();
+            if let Some(winner) = stats.winner {
+                self.tournament.record(round, index, winner, stats.goals);
+                save_tournament(&self.tournament);
+            }
+        }
+        self.show(world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) {
+                return Trans::Quit;
+            }
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop; // it's saved, picked up again from the menu
+            }
+            if is_key_down(event, VirtualKeyCode::N) {
+                return Trans::Switch(Box::new(TournamentSetup::default()));
+            }
+            if is_key_down(event, VirtualKeyCode::Return) {
+                if let Some(pong) = self.next_match(data.world) {
+                    return Trans::Push(Box::new(pong));
+                }
+            }
+        }
+        Trans::None
+    }
+}
+
+impl BracketScreen {
+    // sets up the next fixture, None once there's a champion
+    fn next_match(&mut self, world: &mut World) -> Option<Pong> {
+        let (round, index) = self.tournament.next_fixture()?;
+        let (left, right) = self.tournament.seats(&self.tournament.rounds[round][index]);
+
+        let mut mode = Classic::default();
+        for &(side, entrant) in &[(Side::Left, left), (Side::Right, right)] {
+            if let Some(difficulty) = self.tournament.entrants[entrant].computer {
+                mode = mode.with_computer(side, difficulty);
+            }
+        }
+        // no handicaps in a tournament, everyone plays with the keys for their side
+        let name = |entrant: usize| self.tournament.entrants[entrant].name.as_str();
+        world.insert(Players {
+            left: Player::guest(name(left), Keys::LeftHand),
+            right: Player::guest(name(right), Keys::RightHand),
+        });
+        world.insert(Handicaps::default());
+
+        // someone has to win, a match that would go on forever is played to `points` instead
+        let mut rules = world.write_resource::<Rules>();
+        if let Scoring::Classic { points: 0 } = rules.scoring {
+            let scoring = Scoring::Classic {
+                points: self.tournament.points,
+            };
+            self.scoring = Some(std::mem::replace(&mut rules.scoring, scoring));
+        }
+        self.playing = Some((round, index));
+        Some(Pong::new(Box::new(mode)).popping_when_finished())
+    }
+
+    // the bracket plus what's next, or who won
+    fn text(&self) -> String {
+        let name = |entrant: usize| self.tournament.entrants[entrant].name.as_str();
+        let footer = match (self.tournament.next_fixture(), self.tournament.champion()) {
+            (Some((round, index)), _) => {
+                let (left, right) = self.tournament.seats(&self.tournament.rounds[round][index]);
+                format!("Next: {} (left) v {} (right)", name(left), name(right))
+            }
+            (None, Some(champion)) => format!("Champion: {}", name(champion)),
+            (None, None) => String::new(),
+        };
+        format!("{}\n\n{}", self.tournament.text(), footer)
+    }
+
+    fn show(&mut self, world: &mut World) {
+        let font = world.read_resource::<GameAssets>().font.clone();
+        let title = world
+            .create_entity()
+            .with(UiTransform::new(
+                "bracket_title".to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                -20.,
+                2.,
+                400.,
+                50.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "TOURNAMENT".to_string(),
+                TEXT_COLOR,
+                40.,
+            ))
+            .build();
+
+        let mut text = UiText::new(font.clone(), self.text(), TEXT_COLOR, 11.);
+        text.line_mode = LineMode::Wrap; // a line per fixture
+        let bracket = world
+            .create_entity()
+            .with(UiTransform::new(
+                "bracket".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                -10.,
+                2.,
+                480.,
+                360.,
+            ))
+            .with(text)
+            .build();
+
+        let hint = world
+            .create_entity()
+            .with(UiTransform::new(
+                "bracket_hint".to_string(),
+                Anchor::BottomMiddle,
+                Anchor::BottomMiddle,
+                0.,
+                20.,
+                2.,
+                480.,
+                30.,
+            ))
+            .with(UiText::new(
+                font,
+                "Enter play next  N new tournament  Esc back".to_string(),
+                TEXT_COLOR,
+                16.,
+            ))
+            .build();
+        self.entities = vec![title, bracket, hint];
+    }
+
+    fn hide(&mut self, world: &mut World) {
+        if let Err(err) = world.delete_entities(&self.entities) {
+            warn!("Could not clean up bracket: {}", err);
+        }
+        self.entities.clear();
+    }
+}
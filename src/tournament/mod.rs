@@ -0,0 +1,455 @@
+use amethyst::{config::Config, utils::application_root_dir};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+use crate::ai::Difficulty;
+use crate::pong::Side;
+
+pub use self::bracket::BracketScreen;
+mod bracket;
+
+pub use self::setup::TournamentSetup;
+mod setup;
+
+// written after every match so a tournament can be picked up again on the next start, not checked in
+const TOURNAMENT_FILE: &str = "tournament.ron";
+
+pub const MIN_ENTRANTS: usize = 4;
+pub const MAX_ENTRANTS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    SingleElimination, // lose once and you're out, byes when the entrants don't fill the bracket
+    RoundRobin,        // everyone plays everyone, most wins takes it
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::SingleElimination
+    }
+}
+
+// someone taking part, a human at the keyboard or one of the AI profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entrant {
+    pub name: String,
+    pub computer: Option<Difficulty>, // None for a human
+}
+
+// one match, entrants are indices into Tournament::entrants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub left: usize,
+    pub right: Option<usize>, // None is a bye, `left` goes through without playing
+    pub winner: Option<usize>,
+    pub goals: [u32; 2], // left, right
+}
+
+impl Fixture {
+    fn new(left: usize, right: Option<usize>) -> Self {
+        Fixture {
+            left,
+            right,
+            winner: if right.is_none() { Some(left) } else { None },
+            goals: [0, 0],
+        }
+    }
+}
+
+// a whole tournament, saved after every result
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tournament {
+    pub format: Format,
+    pub entrants: Vec<Entrant>,
+    pub rounds: Vec<Vec<Fixture>>, // single elimination only knows the rounds played so far
+    pub points: u32, // first to this many goals, for matches whose rules would never end
+}
+
+impl Tournament {
+    pub fn new(format: Format, entrants: Vec<Entrant>, points: u32) -> Self {
+        let mut tournament = Tournament {
+            format,
+            entrants,
+            rounds: Vec::new(),
+            points,
+        };
+        tournament.rounds = match format {
+            Format::SingleElimination => vec![tournament.first_round()],
+            Format::RoundRobin => tournament.round_robin(),
+        };
+        tournament
+    }
+
+    // the bracket is filled up to a power of two with byes, the top seeds get them
+    // seeded the usual way, so the top two can only meet in the final
+    fn first_round(&self) -> Vec<Fixture> {
+        let count = self.entrants.len();
+        let mut order = vec![0];
+        while order.len() < count {
+            let size = order.len() * 2;
+            order = order
+                .iter()
+                .flat_map(|&seed| vec![seed, size - 1 - seed])
+                .collect();
+        }
+        order
+            .chunks(2)
+            .map(|pair| Fixture::new(pair[0], Some(pair[1]).filter(|&seed| seed < count)))
+            .collect()
+    }
+
+    // circle method: the first entrant stays put while everyone else rotates round
+    fn round_robin(&self) -> Vec<Vec<Fixture>> {
+        let mut seats = (0..self.entrants.len()).map(Some).collect::<Vec<_>>();
+        if seats.len() % 2 == 1 {
+            seats.push(None); // whoever sits opposite this one has the round off
+        }
+        let count = seats.len();
+        (0..count - 1)
+            .map(|_| {
+                let round = (0..count / 2)
+                    .filter_map(|i| match (seats[i], seats[count - 1 - i]) {
+                        (Some(left), Some(right)) => Some(Fixture::new(left, Some(right))),
+                        _ => None,
+                    })
+                    .collect();
+                seats[1..].rotate_right(1);
+                round
+            })
+            .collect()
+    }
+
+    // the next match to play as (round, fixture), None once the tournament is over
+    pub fn next_fixture(&self) -> Option<(usize, usize)> {
+        self.rounds.iter().enumerate().find_map(|(r, round)| {
+            round
+                .iter()
+                .position(|fixture| fixture.winner.is_none())
+                .map(|f| (r, f))
+        })
+    }
+
+    // which paddle each entrant of a fixture plays, a human against the computer always gets the left
+    pub fn seats(&self, fixture: &Fixture) -> (usize, usize) {
+        let right = fixture.right.unwrap_or(fixture.left);
+        let left_is_computer = self.entrants[fixture.left].computer.is_some();
+        if left_is_computer && self.entrants[right].computer.is_none() {
+            (right, fixture.left)
+        } else {
+            (fixture.left, right)
+        }
+    }
+
+    // `winner` and `goals` are by paddle, as the match was seated
+    pub fn record(&mut self, round: usize, index: usize, winner: Side, goals: [u32; 2]) {
+        let (left, right) = self.seats(&self.rounds[round][index]);
+        let fixture = &mut self.rounds[round][index];
+        fixture.winner = Some(if winner == Side::Left { left } else { right });
+        fixture.goals = if left == fixture.left {
+            goals
+        } else {
+            [goals[1], goals[0]]
+        };
+
+        // once a knockout round is done, its winners meet in the next one
+        if self.format == Format::SingleElimination && self.next_fixture().is_none() {
+            let winners = self
+                .rounds
+                .last()
+                .map(|round| round.iter().filter_map(|f| f.winner).collect::<Vec<_>>())
+                .unwrap_or_default();
+            if winners.len() > 1 {
+                let next = winners
+                    .chunks(2)
+                    .map(|pair| Fixture::new(pair[0], pair.get(1).copied()))
+                    .collect();
+                self.rounds.push(next);
+            }
+        }
+    }
+
+    pub fn champion(&self) -> Option<usize> {
+        if self.next_fixture().is_some() {
+            return None;
+        }
+        match self.format {
+            Format::SingleElimination => self.rounds.last()?.first()?.winner,
+            Format::RoundRobin => self.standings().first().map(|standing| standing.entrant),
+        }
+    }
+
+    // round robin table, most wins first, then goal difference, then seeding
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut table = (0..self.entrants.len())
+            .map(|entrant| Standing {
+                entrant,
+                played: 0,
+                wins: 0,
+                goal_difference: 0,
+            })
+            .collect::<Vec<_>>();
+        for fixture in self.rounds.iter().flatten() {
+            let (right, winner) = match (fixture.right, fixture.winner) {
+                (Some(right), Some(winner)) => (right, winner),
+                _ => continue,
+            };
+            let difference = fixture.goals[0] as i32 - fixture.goals[1] as i32;
+            for &(entrant, difference) in &[(fixture.left, difference), (right, -difference)] {
+                let standing = &mut table[entrant];
+                standing.played += 1;
+                standing.goal_difference += difference;
+                if entrant == winner {
+                    standing.wins += 1;
+                }
+            }
+        }
+        table.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.goal_difference.cmp(&a.goal_difference))
+                .then(a.entrant.cmp(&b.entrant))
+        });
+        table
+    }
+
+    // the bracket or table as lines of text, for the screen between matches
+    // a round robin only shows the round being played, the table sums up the rest
+    pub fn text(&self) -> String {
+        let name = |entrant: usize| self.entrants[entrant].name.as_str();
+        let shown = match self.format {
+            Format::SingleElimination => 0..self.rounds.len(),
+            Format::RoundRobin => {
+                let current = self
+                    .next_fixture()
+                    .map_or(self.rounds.len().saturating_sub(1), |(round, _)| round);
+                current..(current + 1).min(self.rounds.len())
+            }
+        };
+        let mut lines = Vec::new();
+        for (r, round) in self
+            .rounds
+            .iter()
+            .enumerate()
+            .skip(shown.start)
+            .take(shown.len())
+        {
+            lines.push(format!("Round {}", r + 1));
+            for fixture in round {
+                let line = match (fixture.right, fixture.winner) {
+                    (None, _) => format!("  {} - bye", name(fixture.left)),
+                    (Some(right), Some(_)) => format!(
+                        "  {} {}-{} {}",
+                        name(fixture.left),
+                        fixture.goals[0],
+                        fixture.goals[1],
+                        name(right)
+                    ),
+                    (Some(right), None) => format!("  {} v {}", name(fixture.left), name(right)),
+                };
+                lines.push(line);
+            }
+        }
+        if self.format == Format::RoundRobin {
+            lines.push("Table".to_string());
+            for standing in self.standings() {
+                lines.push(format!(
+                    "  {:<14} P{} W{} GD{:+}",
+                    name(standing.entrant),
+                    standing.played,
+                    standing.wins,
+                    standing.goal_difference
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Standing {
+    pub entrant: usize,
+    pub played: u32,
+    pub wins: u32,
+    pub goal_difference: i32,
+}
+
+fn tournament_path() -> amethyst::Result<PathBuf> {
+    Ok(application_root_dir()?.join("config").join(TOURNAMENT_FILE))
+}
+
+// the tournament left from last time, if there is one
+pub fn load_tournament() -> Option<Tournament> {
+    match tournament_path() {
+        Ok(path) if path.exists() => {
+            let tournament = Tournament::load(path);
+            if tournament.entrants.is_empty() {
+                None
+            } else {
+                Some(tournament)
+            }
+        }
+        Ok(_) => None,
+        Err(err) => {
+            warn!("Could not locate saved tournament: {}", err);
+            None
+        }
+    }
+}
+
+pub fn save_tournament(tournament: &Tournament) {
+    let result = tournament_path()
+        .map_err(|err| err.to_string())
+        .and_then(|path| tournament.write(path).map_err(|err| err.to_string()));
+    if let Err(err) = result {
+        warn!("Could not save tournament: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `count` entrants in seeding order, the ones in `computers` played by the AI
+    fn entrants(count: usize, computers: &[usize]) -> Vec<Entrant> {
+        (0..count)
+            .map(|i| Entrant {
+                name: format!("Entrant {}", i + 1),
+                computer: Some(Difficulty::Normal).filter(|_| computers.contains(&i)),
+            })
+            .collect()
+    }
+
+    // `winner` takes the fixture 3-1, whichever paddle they were seated at
+    fn win(tournament: &mut Tournament, round: usize, index: usize, winner: usize) {
+        let (left, _) = tournament.seats(&tournament.rounds[round][index]);
+        if left == winner {
+            tournament.record(round, index, Side::Left, [3, 1]);
+        } else {
+            tournament.record(round, index, Side::Right, [1, 3]);
+        }
+    }
+
+    // plays every fixture with the better seed winning
+    fn favourites_win(tournament: &mut Tournament) {
+        while let Some((round, index)) = tournament.next_fixture() {
+            let fixture = &tournament.rounds[round][index];
+            let favourite = fixture.left.min(fixture.right.unwrap());
+            win(tournament, round, index, favourite);
+        }
+    }
+
+    #[test]
+    fn byes_go_to_the_top_seeds() {
+        for &count in &[4, 5, 8, 16] {
+            let tournament = Tournament::new(Format::SingleElimination, entrants(count, &[]), 5);
+            let first = &tournament.rounds[0];
+            assert_eq!(first.len(), count.next_power_of_two() / 2);
+            let mut byes = first
+                .iter()
+                .filter(|fixture| fixture.right.is_none())
+                .map(|fixture| fixture.left)
+                .collect::<Vec<_>>();
+            byes.sort();
+            let expected = (0..count.next_power_of_two() - count).collect::<Vec<_>>();
+            assert_eq!(byes, expected, "{} entrants", count);
+
+            // everyone is in the first round exactly once
+            let mut seen = first
+                .iter()
+                .flat_map(|fixture| vec![Some(fixture.left), fixture.right])
+                .flatten()
+                .collect::<Vec<_>>();
+            seen.sort();
+            assert_eq!(seen, (0..count).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn the_top_two_seeds_can_only_meet_in_the_final() {
+        for &count in &[4, 5, 8, 16] {
+            let mut tournament =
+                Tournament::new(Format::SingleElimination, entrants(count, &[]), 5);
+            favourites_win(&mut tournament);
+            let (last, earlier) = tournament.rounds.split_last().unwrap();
+            for fixture in earlier.iter().flatten() {
+                let pair = (fixture.left, fixture.right);
+                assert!(
+                    pair != (0, Some(1)) && pair != (1, Some(0)),
+                    "seeds 1 and 2 met early with {} entrants",
+                    count
+                );
+            }
+            assert_eq!(last.len(), 1);
+            assert_eq!((last[0].left, last[0].right), (0, Some(1)));
+        }
+    }
+
+    #[test]
+    fn round_robin_with_an_odd_count_pairs_everyone_once() {
+        let count = 5;
+        let tournament = Tournament::new(Format::RoundRobin, entrants(count, &[]), 5);
+        assert_eq!(tournament.rounds.len(), count);
+        let mut met = vec![vec![0; count]; count];
+        for round in &tournament.rounds {
+            let mut playing = vec![false; count];
+            for fixture in round {
+                let right = fixture.right.unwrap();
+                for &entrant in &[fixture.left, right] {
+                    assert!(
+                        !playing[entrant],
+                        "entrant {} plays twice in a round",
+                        entrant
+                    );
+                    playing[entrant] = true;
+                }
+                met[fixture.left][right] += 1;
+                met[right][fixture.left] += 1;
+            }
+            // one entrant has the round off
+            assert_eq!(playing.iter().filter(|&&playing| !playing).count(), 1);
+        }
+        for (a, row) in met.iter().enumerate() {
+            for (b, &times) in row.iter().enumerate() {
+                assert_eq!(times, if a == b { 0 } else { 1 }, "{} v {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn a_human_seated_left_against_the_fixtures_left_computer_gets_the_goals_swapped() {
+        // round one of a four entrant round robin is 1 v 4 and 2 v 3
+        let mut tournament = Tournament::new(Format::RoundRobin, entrants(4, &[0]), 5);
+        let fixture = tournament.rounds[0][0].clone();
+        assert_eq!((fixture.left, fixture.right), (0, Some(3)));
+        assert_eq!(tournament.seats(&fixture), (3, 0));
+
+        // the human at the left paddle wins 5-2
+        tournament.record(0, 0, Side::Left, [5, 2]);
+        let fixture = &tournament.rounds[0][0];
+        assert_eq!(fixture.winner, Some(3));
+        assert_eq!(fixture.goals, [2, 5]);
+        let standings = tournament.standings();
+        let human = standings.iter().find(|s| s.entrant == 3).unwrap();
+        assert_eq!((human.wins, human.goal_difference), (1, 3));
+    }
+
+    #[test]
+    fn the_champion_is_crowned_once_the_last_round_is_complete() {
+        let mut tournament = Tournament::new(Format::SingleElimination, entrants(4, &[]), 5);
+        win(&mut tournament, 0, 0, 0);
+        win(&mut tournament, 0, 1, 2);
+        assert_eq!(tournament.rounds.len(), 2);
+        assert_eq!(tournament.champion(), None);
+        win(&mut tournament, 1, 0, 2);
+        assert_eq!(tournament.next_fixture(), None);
+        assert_eq!(tournament.champion(), Some(2));
+
+        let mut tournament = Tournament::new(Format::RoundRobin, entrants(5, &[]), 5);
+        assert_eq!(tournament.champion(), None);
+        favourites_win(&mut tournament);
+        assert_eq!(tournament.champion(), Some(0));
+    }
+}
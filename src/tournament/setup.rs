@@ -0,0 +1,250 @@
+use amethyst::{
+    ecs::prelude::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, UiText, UiTransform},
+};
+
+use log::warn;
+
+use super::{
+    save_tournament, BracketScreen, Entrant, Format, Tournament, MAX_ENTRANTS, MIN_ENTRANTS,
+};
+use crate::ai::AiConfig;
+use crate::loading::GameAssets;
+
+const SELECTED_COLOR: [f32; 4] = [1., 1., 0., 1.];
+const NORMAL_COLOR: [f32; 4] = [1., 1., 1., 1.];
+
+const MAX_POINTS: u32 = 21;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Format,
+    Entrants,
+    Humans,
+    Points,
+}
+
+const ROWS: [Row; 4] = [Row::Format, Row::Entrants, Row::Humans, Row::Points];
+
+// picks how a new tournament is played and who is in it, then hands over to the bracket
+// entrants that aren't human are filled in from the AI profiles in config/ai.ron
+pub struct TournamentSetup {
+    format: Format,
+    entrants: usize,
+    humans: usize,
+    points: u32,
+    selected: usize,
+    rows: Vec<Entity>, // one line of text per entry in ROWS
+    labels: Vec<Entity>,
+}
+
+impl Default for TournamentSetup {
+    fn default() -> Self {
+        TournamentSetup {
+            format: Format::SingleElimination,
+            entrants: 8,
+            humans: 2,
+            points: 5,
+            selected: 0,
+            rows: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl SimpleState for TournamentSetup {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        let title = world
+            .create_entity()
+            .with(UiTransform::new(
+                "tournament_setup_title".to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                -20.,
+                2.,
+                400.,
+                50.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "NEW TOURNAMENT".to_string(),
+                NORMAL_COLOR,
+                40.,
+            ))
+            .build();
+        let hint = world
+            .create_entity()
+            .with(UiTransform::new(
+                "tournament_setup_hint".to_string(),
+                Anchor::BottomMiddle,
+                Anchor::BottomMiddle,
+                0.,
+                20.,
+                2.,
+                480.,
+                30.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                "Up/Down select  Left/Right adjust  Enter start  Esc back".to_string(),
+                NORMAL_COLOR,
+                16.,
+            ))
+            .build();
+        self.labels = vec![title, hint];
+
+        self.rows = ROWS
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        format!("tournament_setup_{:?}", row),
+                        Anchor::Middle,
+                        Anchor::Middle,
+                        0.,
+                        90. - 60. * i as f32,
+                        2.,
+                        480.,
+                        40.,
+                    ))
+                    .with(UiText::new(font.clone(), String::new(), NORMAL_COLOR, 24.))
+                    .build()
+            })
+            .collect();
+
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let entities = self
+            .rows
+            .drain(..)
+            .chain(self.labels.drain(..))
+            .collect::<Vec<_>>();
+        if let Err(err) = data.world.delete_entities(&entities) {
+            warn!("Could not clean up tournament setup screen: {}", err);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) {
+                return Trans::Quit;
+            }
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if is_key_down(event, VirtualKeyCode::Return) {
+                let tournament = self.tournament(&data.world.read_resource::<AiConfig>());
+                save_tournament(&tournament);
+                return Trans::Switch(Box::new(BracketScreen::new(tournament)));
+            }
+
+            let row = ROWS[self.selected];
+            if is_key_down(event, VirtualKeyCode::Up) {
+                self.selected = (self.selected + ROWS.len() - 1) % ROWS.len();
+            } else if is_key_down(event, VirtualKeyCode::Down) {
+                self.selected = (self.selected + 1) % ROWS.len();
+            } else if is_key_down(event, VirtualKeyCode::Left) {
+                self.adjust(row, -1);
+            } else if is_key_down(event, VirtualKeyCode::Right) {
+                self.adjust(row, 1);
+            } else {
+                return Trans::None;
+            }
+            self.refresh(data.world);
+        }
+        Trans::None
+    }
+}
+
+impl TournamentSetup {
+    fn adjust(&mut self, row: Row, steps: i32) {
+        let step = |value: usize, min: usize, max: usize| {
+            (value as i32 + steps).max(min as i32).min(max as i32) as usize
+        };
+        match row {
+            Row::Format => {
+                self.format = match self.format {
+                    Format::SingleElimination => Format::RoundRobin,
+                    Format::RoundRobin => Format::SingleElimination,
+                }
+            }
+            Row::Entrants => {
+                self.entrants = step(self.entrants, MIN_ENTRANTS, MAX_ENTRANTS);
+                self.humans = self.humans.min(self.entrants);
+            }
+            Row::Humans => self.humans = step(self.humans, 0, self.entrants),
+            Row::Points => self.points = step(self.points as usize, 1, MAX_POINTS as usize) as u32,
+        }
+    }
+
+    // humans first, as "Player 1", "Player 2"..., then computers taking the profiles in turn
+    fn tournament(&self, config: &AiConfig) -> Tournament {
+        let mut entrants = (1..=self.humans)
+            .map(|i| Entrant {
+                name: format!("Player {}", i),
+                computer: None,
+            })
+            .collect::<Vec<_>>();
+        let profiles = &config.profiles;
+        for i in 0..self.entrants - self.humans {
+            let entrant = match profiles.get(i % profiles.len().max(1)) {
+                // the same profile again gets a number, "Rookie 2"
+                Some(profile) if i >= profiles.len() => Entrant {
+                    name: format!("{} {}", profile.name, i / profiles.len() + 1),
+                    computer: Some(profile.difficulty),
+                },
+                Some(profile) => Entrant {
+                    name: profile.name.clone(),
+                    computer: Some(profile.difficulty),
+                },
+                None => Entrant {
+                    name: format!("Computer {}", i + 1),
+                    computer: Some(config.difficulty),
+                },
+            };
+            entrants.push(entrant);
+        }
+        Tournament::new(self.format, entrants, self.points)
+    }
+
+    // redraws every row from the current choices
+    fn refresh(&self, world: &mut World) {
+        let mut texts = world.write_storage::<UiText>();
+        for (i, (row, entity)) in ROWS.iter().zip(&self.rows).enumerate() {
+            if let Some(text) = texts.get_mut(*entity) {
+                text.text = match row {
+                    Row::Format => match self.format {
+                        Format::SingleElimination => "Knockout".to_string(),
+                        Format::RoundRobin => "Round robin".to_string(),
+                    },
+                    Row::Entrants => format!("{} entrants", self.entrants),
+                    Row::Humans => format!(
+                        "{} human, {} computer",
+                        self.humans,
+                        self.entrants - self.humans
+                    ),
+                    Row::Points => format!("First to {}", self.points),
+                };
+                text.color = if i == self.selected {
+                    SELECTED_COLOR
+                } else {
+                    NORMAL_COLOR
+                };
+            }
+        }
+    }
+}